@@ -0,0 +1,141 @@
+use stats::{first_balls, spare_attempts, Conversion};
+use {Draw, Game};
+
+// Bowling-ball arsenal
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum BallRole {
+    /// Ball thrown first on a fresh pattern
+    Benchmark,
+    Strong,
+    Weak,
+    /// Plastic ball for spares
+    Spare,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Ball {
+    pub name: String,
+    pub role: BallRole,
+}
+
+/// What a ball did, over the draws tagged with it
+#[derive(PartialEq, Eq, Debug, Default, Clone, Copy)]
+pub struct BallStats {
+    /// First balls and the strikes among them
+    pub strikes: Conversion,
+    /// First balls recorded as pocket hits and the strikes among them
+    pub carry: Conversion,
+    /// Spare attempts thrown with the ball and the spares made
+    pub spares: Conversion,
+    /// Games where the ball was thrown at least once
+    pub games: u32,
+}
+
+/// Balls of one bowler, draws are tagged with their index here
+#[derive(Default)]
+pub struct Arsenal {
+    balls: Vec<Ball>,
+}
+
+impl Arsenal {
+    pub fn new() -> Arsenal {
+        Arsenal { balls: Vec::new() }
+    }
+
+    pub fn add_ball(&mut self, name: &str, role: BallRole) -> usize {
+        self.balls.push(Ball { name: name.to_string(), role });
+        self.balls.len() - 1
+    }
+
+    pub fn balls(&self) -> &[Ball] {
+        &self.balls
+    }
+
+    /// Statistics of every ball of the arsenal, in the same order
+    ///
+    /// Draws without a ball, or tagged with a ball outside the arsenal, are
+    /// left out.
+    pub fn stats<'a, I: IntoIterator<Item = &'a Game>>(&self, games: I) -> Vec<BallStats> {
+        let count = self.balls.len();
+        let mut stats = vec![BallStats::default(); count];
+        for game in games {
+            let mut used = vec![false; count];
+            for frame in game.frames() {
+                let (draws, shots) = (frame.draws(), frame.shots());
+                let ball = |index: usize| shots[index].ball.filter(|&ball| ball < count);
+                for first in first_balls(draws) {
+                    if let Some(ball) = ball(first) {
+                        let strike = draws[first] == Draw::Strike;
+                        stats[ball].strikes.add(strike);
+                        if shots[first].pocket == Some(true) {
+                            stats[ball].carry.add(strike);
+                        }
+                    }
+                }
+                for (_, attempt) in spare_attempts(draws) {
+                    if let Some(ball) = ball(attempt) {
+                        stats[ball].spares.add(draws[attempt] == Draw::Spare);
+                    }
+                }
+                for index in 0..draws.len() {
+                    if let Some(ball) = ball(index) {
+                        used[ball] = true;
+                    }
+                }
+            }
+            for (ball, used) in used.into_iter().enumerate() {
+                if used {
+                    stats[ball].games += 1;
+                }
+            }
+        }
+        stats
+    }
+
+    /// Balls thrown in at least `limit` games, due for resurfacing
+    pub fn to_resurface<'a, I: IntoIterator<Item = &'a Game>>(&self, games: I, limit: u32) -> Vec<usize> {
+        self.stats(games).iter()
+            .enumerate()
+            .filter(|&(_, stats)| stats.games >= limit)
+            .map(|(ball, _)| ball)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn per_ball_stats() {
+        let mut arsenal = Arsenal::new();
+        let strong = arsenal.add_ball("Phaze", BallRole::Strong);
+        let plastic = arsenal.add_ball("Viz-A-Ball", BallRole::Spare);
+        assert_eq!(arsenal.balls()[plastic].role, BallRole::Spare);
+        let mut game = Game::new();
+        for &(first, pocket, second) in &[
+            (Draw::Strike, true, None),
+            (Draw::Open(9), true, Some(Draw::Spare)),
+            (Draw::Open(8), false, Some(Draw::Open(1))),
+            (Draw::Strike, false, None),
+        ] {
+            game.set_draw(first);
+            game.set_ball(strong);
+            game.set_pocket(pocket);
+            if let Some(second) = second {
+                game.set_draw(second);
+                game.set_ball(plastic);
+            }
+        }
+        let games = vec![game, Game::from_draws(&[Draw::Strike])];
+        let stats = arsenal.stats(&games);
+        assert_eq!(stats[strong].strikes, Conversion { attempts: 4, converted: 2 });
+        assert_eq!(stats[strong].carry, Conversion { attempts: 2, converted: 1 });
+        assert_eq!(stats[plastic].spares.percentage(), 50.);
+        assert_eq!(stats[plastic].strikes.attempts, 0);
+        assert_eq!(stats[strong].games, 1);
+        assert_eq!(arsenal.to_resurface(&games, 1), vec![strong, plastic]);
+        assert!(arsenal.to_resurface(&games, 2).is_empty());
+    }
+}
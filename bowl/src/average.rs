@@ -0,0 +1,135 @@
+use Game;
+
+// League averages and handicap
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Rounding {
+    /// Fractions dropped, the usual league rule
+    Truncate,
+    Nearest,
+    Up,
+}
+
+/// Where an average comes from
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum AverageSource {
+    Bowled,
+    /// Carried over from the previous season until enough games are bowled
+    Entering,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Average {
+    pub pins: u32,
+    /// Finished games bowled, even when the entering average is used
+    pub games: usize,
+    pub source: AverageSource,
+}
+
+/// How a league computes averages
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct AverageRules {
+    pub rounding: Rounding,
+    /// Games to bowl before the bowled average replaces the entering one
+    pub minimum_games: usize,
+}
+
+impl Default for AverageRules {
+    fn default() -> AverageRules {
+        AverageRules {
+            rounding: Rounding::Truncate,
+            minimum_games: 1,
+        }
+    }
+}
+
+impl AverageRules {
+    /// Average of the finished games, or the entering average before the
+    /// minimum number of games, `None` when neither is available
+    pub fn average<'a, I: IntoIterator<Item = &'a Game>>(&self, games: I, entering: Option<u32>) -> Option<Average> {
+        let scores: Vec<u32> = games.into_iter()
+            .filter(|game| game.is_finished())
+            .map(|game| game.score().value())
+            .collect();
+        let count = scores.len();
+        if count == 0 || count < self.minimum_games {
+            return entering.map(|pins| Average {
+                pins,
+                games: count,
+                source: AverageSource::Entering,
+            });
+        }
+        let total: u32 = scores.iter().sum();
+        let count32 = count as u32;
+        let pins = match self.rounding {
+            Rounding::Truncate => total / count32,
+            Rounding::Nearest => (total * 2 + count32) / (count32 * 2),
+            Rounding::Up => total.div_ceil(count32),
+        };
+        Some(Average {
+            pins,
+            games: count,
+            source: AverageSource::Bowled,
+        })
+    }
+}
+
+/// Handicap as a percentage of the difference between a base score and the average
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Handicap {
+    pub base: u32,
+    pub percentage: u32,
+}
+
+impl Handicap {
+    /// Handicap for one game, fractions dropped, nothing above the base
+    pub fn handicap(&self, average: u32) -> u32 {
+        self.base.saturating_sub(average) * self.percentage / 100
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixtures::game;
+    use Draw;
+
+    #[test]
+    fn rounding_rules() {
+        let games = vec![game(8), game(8), game(7)];
+        let mut rules = AverageRules::default();
+        assert_eq!(rules.average(&games, None).unwrap().pins, 153);
+        rules.rounding = Rounding::Nearest;
+        assert_eq!(rules.average(&games, None).unwrap().pins, 153);
+        rules.rounding = Rounding::Up;
+        assert_eq!(rules.average(&games, None).unwrap().pins, 154);
+        let games = vec![game(8), game(8), game(9)];
+        rules.rounding = Rounding::Nearest;
+        assert_eq!(rules.average(&games, None).unwrap().pins, 167);
+    }
+
+    #[test]
+    fn entering_average_until_minimum() {
+        let rules = AverageRules { rounding: Rounding::Truncate, minimum_games: 3 };
+        let mut games = vec![game(9), game(9), Game::from_draws(&[Draw::Strike])];
+        assert_eq!(rules.average(&games, None), None);
+        assert_eq!(rules.average(&games, Some(170)), Some(Average {
+            pins: 170,
+            games: 2,
+            source: AverageSource::Entering,
+        }));
+        games.push(game(6));
+        assert_eq!(rules.average(&games, Some(170)), Some(Average {
+            pins: 160,
+            games: 3,
+            source: AverageSource::Bowled,
+        }));
+    }
+
+    #[test]
+    fn handicap() {
+        let handicap = Handicap { base: 220, percentage: 90 };
+        assert_eq!(handicap.handicap(153), 60);
+        assert_eq!(handicap.handicap(230), 0);
+    }
+}
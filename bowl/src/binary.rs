@@ -0,0 +1,259 @@
+use std::fmt::{Display, Error as FmtError, Formatter};
+use std::io::{self, Read};
+
+use {Draw, Game};
+
+// Compact binary encoding of games
+
+/// Version byte starting every encoded game
+pub const VERSION: u8 = 1;
+
+/// Draws of a full game at most, twelve strikes down to twenty-one balls
+const MAX_DRAWS: usize = 21;
+
+/// Bits taken by one draw
+const DRAW_BITS: usize = 5;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// Version byte other than `VERSION`
+    Version(u8),
+    /// Data ended in the middle of a game
+    Truncated,
+    /// Draw that cannot be decoded or cannot be played where it is, counted from 0
+    Draw(usize),
+    /// More draws than a game holds, or padding bits set
+    Malformed,
+    /// Bytes left after a game decoded alone
+    Trailing,
+}
+
+impl Display for Error {
+    fn fmt(&self, formatter: &mut Formatter) -> Result<(), FmtError> {
+        match *self {
+            Error::Io(ref error) => write!(formatter, "{}", error),
+            Error::Version(version) => write!(formatter, "unknown version {}", version),
+            Error::Truncated => write!(formatter, "truncated game"),
+            Error::Draw(index) => write!(formatter, "invalid draw {}", index),
+            Error::Malformed => write!(formatter, "malformed game"),
+            Error::Trailing => write!(formatter, "bytes after the game"),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Error {
+        Error::Io(error)
+    }
+}
+
+fn code(draw: Draw) -> u8 {
+    match draw {
+        Draw::Open(x) => x as u8,
+        Draw::Split(x) => 10 + x as u8,
+        Draw::Spare => 19,
+        Draw::Strike => 20,
+        Draw::Fool => 21,
+    }
+}
+
+fn draw(code: u8) -> Option<Draw> {
+    match code {
+        0..=9 => Some(Draw::Open(code as u32)),
+        10..=18 => Some(Draw::Split(code as u32 - 10)),
+        19 => Some(Draw::Spare),
+        20 => Some(Draw::Strike),
+        21 => Some(Draw::Fool),
+        _ => None,
+    }
+}
+
+fn packed_len(draws: usize) -> usize {
+    (draws * DRAW_BITS).div_ceil(8)
+}
+
+/// The draws of a game, without what was recorded about them, lane or pattern
+///
+/// A version byte, the number of draws, then every draw on 5 bits, least
+/// significant bits first: 0 to 9 for an open draw, 10 to 18 for a split of
+/// 0 to 8 pins, 19 spare, 20 strike and 21 fool. Unused bits of the last byte
+/// are 0. A full game takes at most 16 bytes.
+///
+/// Draws are checked as `decode` does, the first one that cannot be played
+/// where it is refuses the game with `Error::Draw`. Every encoded game
+/// decodes again.
+pub fn encode(game: &Game) -> Result<Vec<u8>, Error> {
+    let draws: Vec<Draw> = game.frames().iter()
+        .flat_map(|frame| frame.draws().to_vec())
+        .collect();
+    let mut bytes = vec![VERSION, draws.len() as u8];
+    let start = bytes.len();
+    bytes.resize(start + packed_len(draws.len()), 0);
+    let mut replayed = Game::new();
+    for (index, &draw) in draws.iter().enumerate() {
+        if !replayed.accepts(draw) {
+            return Err(Error::Draw(index));
+        }
+        replayed.set_draw(draw);
+        let bit = index * DRAW_BITS;
+        let value = (code(draw) as u16) << (bit % 8);
+        bytes[start + bit / 8] |= value as u8;
+        if value > 0xff {
+            bytes[start + bit / 8 + 1] |= (value >> 8) as u8;
+        }
+    }
+    Ok(bytes)
+}
+
+fn decode_draws(count: usize, packed: &[u8]) -> Result<Game, Error> {
+    let mut game = Game::new();
+    for index in 0..count {
+        let bit = index * DRAW_BITS;
+        let low = packed[bit / 8] as u16;
+        let high = packed.get(bit / 8 + 1).cloned().unwrap_or(0) as u16;
+        let code = (((high << 8) | low) >> (bit % 8)) as u8 & 0x1f;
+        match draw(code) {
+            Some(draw) if game.accepts(draw) => {
+                game.set_draw(draw);
+            },
+            _ => return Err(Error::Draw(index)),
+        }
+    }
+    let used = count * DRAW_BITS;
+    if !used.is_multiple_of(8) && packed[used / 8] >> (used % 8) != 0 {
+        return Err(Error::Malformed);
+    }
+    Ok(game)
+}
+
+/// Decode one game taking the whole of `bytes`, every draw is checked
+pub fn decode(bytes: &[u8]) -> Result<Game, Error> {
+    let (version, count) = match *bytes {
+        [version, count, ..] => (version, count as usize),
+        _ => return Err(Error::Truncated),
+    };
+    if version != VERSION {
+        return Err(Error::Version(version));
+    }
+    if count > MAX_DRAWS {
+        return Err(Error::Malformed);
+    }
+    let end = 2 + packed_len(count);
+    if bytes.len() < end {
+        return Err(Error::Truncated);
+    }
+    if bytes.len() > end {
+        return Err(Error::Trailing);
+    }
+    decode_draws(count, &bytes[2..])
+}
+
+/// Games read one after the other from encoded games put end to end
+///
+/// Reading stops after the first error.
+pub struct Reader<R: Read> {
+    input: R,
+    failed: bool,
+}
+
+impl<R: Read> Reader<R> {
+    pub fn new(input: R) -> Reader<R> {
+        Reader { input, failed: false }
+    }
+
+    fn read_game(&mut self) -> Result<Option<Game>, Error> {
+        let mut header = [0; 2];
+        if self.input.read(&mut header[..1])? == 0 {
+            return Ok(None);
+        }
+        match self.input.read_exact(&mut header[1..]) {
+            Err(ref error) if error.kind() == io::ErrorKind::UnexpectedEof => return Err(Error::Truncated),
+            result => result?,
+        }
+        if header[0] != VERSION {
+            return Err(Error::Version(header[0]));
+        }
+        let count = header[1] as usize;
+        if count > MAX_DRAWS {
+            return Err(Error::Malformed);
+        }
+        let mut packed = vec![0; packed_len(count)];
+        match self.input.read_exact(&mut packed) {
+            Err(ref error) if error.kind() == io::ErrorKind::UnexpectedEof => return Err(Error::Truncated),
+            result => result?,
+        }
+        decode_draws(count, &packed).map(Some)
+    }
+}
+
+impl<R: Read> Iterator for Reader<R> {
+    type Item = Result<Game, Error>;
+
+    fn next(&mut self) -> Option<Result<Game, Error>> {
+        if self.failed {
+            return None;
+        }
+        let game = self.read_game();
+        self.failed = game.is_err();
+        game.transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixtures::game;
+    use Pins;
+
+    #[test]
+    fn round_trip() {
+        let perfect = Game::from_draws(&[Draw::Strike; 12]);
+        let bytes = encode(&perfect).unwrap();
+        assert_eq!(bytes.len(), 10);
+        assert_eq!(decode(&bytes).unwrap().score(), Pins::FinalPins(300));
+        let mut draws = vec![Draw::Split(8), Draw::Spare, Draw::Fool, Draw::Open(9)];
+        draws.extend_from_slice(&[Draw::Open(4); 15]);
+        draws.extend_from_slice(&[Draw::Spare, Draw::Strike]);
+        let game = Game::from_draws(&draws);
+        let bytes = encode(&game).unwrap();
+        assert_eq!(bytes.len(), 16);
+        let decoded = decode(&bytes).unwrap();
+        assert_eq!(decoded.score(), game.score());
+        assert_eq!(encode(&decoded).unwrap(), bytes);
+        assert_eq!(decode(&encode(&Game::new()).unwrap()).unwrap().frames().len(), 0);
+    }
+
+    #[test]
+    fn validation() {
+        let bytes = encode(&Game::from_draws(&[Draw::Open(7), Draw::Open(2)])).unwrap();
+        assert_eq!(bytes, vec![1, 2, 0x47, 0]);
+        match decode(&[2, 0]) {
+            Err(Error::Version(2)) => {},
+            other => panic!("{:?}", other.map(|game| game.score())),
+        }
+        assert!(matches!(decode(&bytes[..3]), Err(Error::Truncated)));
+        assert!(matches!(decode(&[1, 2, 0x47, 0, 0]), Err(Error::Trailing)));
+        // 7 then 3 knocks down too many pins
+        assert!(matches!(decode(&[1, 2, 0x67, 0]), Err(Error::Draw(1))));
+        assert!(matches!(decode(&[1, 2, 0x47, 0x80]), Err(Error::Malformed)));
+        assert!(matches!(decode(&[1, 22]), Err(Error::Malformed)));
+        assert!(matches!(encode(&Game::from_draws(&[Draw::Open(10)])), Err(Error::Draw(0))));
+        assert!(matches!(encode(&Game::from_draws(&[Draw::Strike, Draw::Split(22)])), Err(Error::Draw(1))));
+        assert!(matches!(encode(&Game::from_draws(&[Draw::Open(7), Draw::Open(5)])), Err(Error::Draw(1))));
+    }
+
+    #[test]
+    fn streaming() {
+        let mut file = Vec::new();
+        for pins in 0..3 {
+            file.extend(encode(&game(pins)).unwrap());
+        }
+        let scores: Vec<u32> = Reader::new(&file[..]).map(|game| game.unwrap().score().value()).collect();
+        assert_eq!(scores, vec![0, 20, 40]);
+        file.push(VERSION);
+        let games: Vec<Result<Game, Error>> = Reader::new(&file[..]).collect();
+        assert_eq!(games.len(), 4);
+        assert!(matches!(games[3], Err(Error::Truncated)));
+    }
+}
@@ -0,0 +1,332 @@
+use std::cmp::Reverse;
+
+use {Draw, Game};
+
+// Single-elimination head-to-head brackets
+
+/// A bowler entered in a bracket
+pub struct Entrant {
+    pub name: String,
+    /// Qualifying score, used for seeding
+    pub qualifying: u32,
+    /// Handicap added to each game of the bracket
+    pub handicap: u32,
+}
+
+/// How a tied match is decided, roll-offs are bowled scratch
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Rolloff {
+    /// Ninth and tenth frames bowled and scored as usual
+    NinthAndTenth,
+    /// One ball each, most pins wins
+    OneBall,
+}
+
+impl Rolloff {
+    pub fn score(&self, draws: &[Draw]) -> u32 {
+        match *self {
+            Rolloff::NinthAndTenth => {
+                let mut game = Game::from_draws(&[Draw::Fool; 16]);
+                for draw in draws {
+                    game.set_draw(*draw);
+                }
+                game.score().value()
+            },
+            Rolloff::OneBall => Game::from_draws(draws).frames()
+                .first()
+                .map(|frame| frame.sum_n_draws(1).value())
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// A head-to-head match, entrants are given by seed
+pub struct Match {
+    pub entrants: (Option<usize>, Option<usize>),
+    /// Games bowled, scored with handicap
+    pub games: Vec<(u32, u32)>,
+    pub rolloffs: Vec<(u32, u32)>,
+    pub winner: Option<usize>,
+}
+
+impl Match {
+    fn new() -> Match {
+        Match {
+            entrants: (None, None),
+            games: Vec::new(),
+            rolloffs: Vec::new(),
+            winner: None,
+        }
+    }
+
+    fn wins(&self) -> (usize, usize) {
+        self.games.iter().fold((0, 0), |wins, &(home, away)| {
+            if home > away {
+                (wins.0 + 1, wins.1)
+            } else if home < away {
+                (wins.0, wins.1 + 1)
+            } else {
+                wins
+            }
+        })
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.entrants.0.is_some() && self.entrants.1.is_some() && self.winner.is_none()
+    }
+
+    /// Every game is bowled and nobody won the majority
+    pub fn needs_rolloff(&self, best_of: usize) -> bool {
+        self.is_ready() && self.games.len() == best_of
+    }
+
+    fn decide(&mut self, best_of: usize) {
+        let (home, away) = self.wins();
+        if home > best_of / 2 || (home > away && self.games.len() == best_of) {
+            self.winner = self.entrants.0;
+        } else if away > best_of / 2 || (away > home && self.games.len() == best_of) {
+            self.winner = self.entrants.1;
+        } else if let Some(&(home, away)) = self.rolloffs.last() {
+            if home > away {
+                self.winner = self.entrants.0;
+            } else if home < away {
+                self.winner = self.entrants.1;
+            }
+        }
+    }
+
+    fn loser(&self) -> Option<usize> {
+        match self.winner {
+            Some(winner) if Some(winner) == self.entrants.0 => self.entrants.1,
+            Some(_) => self.entrants.0,
+            None => None,
+        }
+    }
+}
+
+/// Final position of an entrant, losers of the same round share their place
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Placing {
+    pub place: usize,
+    pub entrant: usize,
+}
+
+/// Single-elimination bracket seeded from qualifying scores
+///
+/// The field is filled up to a power of two with byes given to the best seeds,
+/// and seeds 1 and 2 can only meet in the final.
+pub struct Bracket {
+    entrants: Vec<Entrant>,
+    best_of: usize,
+    rolloff: Rolloff,
+    rounds: Vec<Vec<Match>>,
+}
+
+impl Bracket {
+    /// Seed the entrants, highest qualifying score first and ties in entry order
+    pub fn new(mut entrants: Vec<Entrant>, best_of: usize, rolloff: Rolloff) -> Bracket {
+        if entrants.len() < 2 || best_of == 0 {
+            panic!("Shouldn't append");
+        }
+        entrants.sort_by_key(|entrant| Reverse(entrant.qualifying));
+        let size = entrants.len().next_power_of_two();
+        let mut order = vec![0];
+        while order.len() < size {
+            let len = order.len() * 2;
+            order = order.iter().flat_map(|&seed| vec![seed, len - 1 - seed]).collect();
+        }
+        let mut rounds = Vec::new();
+        let mut matches = size / 2;
+        while matches > 0 {
+            rounds.push((0..matches).map(|_| Match::new()).collect::<Vec<_>>());
+            matches /= 2;
+        }
+        let mut bracket = Bracket {
+            entrants,
+            best_of,
+            rolloff,
+            rounds,
+        };
+        let count = bracket.entrants.len();
+        for (index, pair) in order.chunks(2).enumerate() {
+            let seed = |seed: usize| if seed < count { Some(seed) } else { None };
+            let game = &mut bracket.rounds[0][index];
+            game.entrants = (seed(pair[0]), seed(pair[1]));
+            if game.entrants.1.is_none() {
+                game.winner = game.entrants.0;
+            }
+        }
+        for index in 0..size / 2 {
+            bracket.advance(0, index);
+        }
+        bracket
+    }
+
+    /// Entrants in seed order
+    pub fn entrants(&self) -> &[Entrant] {
+        self.entrants.as_slice()
+    }
+
+    pub fn rounds(&self) -> &[Vec<Match>] {
+        self.rounds.as_slice()
+    }
+
+    /// Matches waiting for a game or a roll-off, as `(round, index)`
+    pub fn pending(&self) -> Vec<(usize, usize)> {
+        let mut pending = Vec::new();
+        for (round, matches) in self.rounds.iter().enumerate() {
+            for (index, game) in matches.iter().enumerate() {
+                if game.is_ready() {
+                    pending.push((round, index));
+                }
+            }
+        }
+        pending
+    }
+
+    pub fn needs_rolloff(&self, round: usize, index: usize) -> bool {
+        self.rounds[round][index].needs_rolloff(self.best_of)
+    }
+
+    /// Record one finished game of a match, `home` is the game of `entrants.0`
+    pub fn record_game(&mut self, round: usize, index: usize, home: Game, away: Game) {
+        if !home.is_finished() || !away.is_finished() || !self.rounds[round][index].is_ready()
+                || self.needs_rolloff(round, index) {
+            panic!("Shouldn't append");
+        }
+        let best_of = self.best_of;
+        let handicap = |seed: Option<usize>| seed.map(|seed| self.entrants[seed].handicap).unwrap_or(0);
+        let handicaps = (handicap(self.rounds[round][index].entrants.0),
+                         handicap(self.rounds[round][index].entrants.1));
+        let game = &mut self.rounds[round][index];
+        game.games.push((home.score().value() + handicaps.0, away.score().value() + handicaps.1));
+        game.decide(best_of);
+        self.advance(round, index);
+    }
+
+    /// Record a roll-off of a tied match, bowled with the bracket's roll-off rule
+    pub fn record_rolloff(&mut self, round: usize, index: usize, home: &[Draw], away: &[Draw]) {
+        if !self.needs_rolloff(round, index) {
+            panic!("Shouldn't append");
+        }
+        let scores = (self.rolloff.score(home), self.rolloff.score(away));
+        let best_of = self.best_of;
+        let game = &mut self.rounds[round][index];
+        game.rolloffs.push(scores);
+        game.decide(best_of);
+        self.advance(round, index);
+    }
+
+    fn advance(&mut self, round: usize, index: usize) {
+        let winner = match self.rounds[round][index].winner {
+            Some(winner) => winner,
+            None => return,
+        };
+        if let Some(next) = self.rounds.get_mut(round + 1) {
+            let game = &mut next[index / 2];
+            if index.is_multiple_of(2) {
+                game.entrants.0 = Some(winner);
+            } else {
+                game.entrants.1 = Some(winner);
+            }
+        }
+    }
+
+    pub fn winner(&self) -> Option<usize> {
+        self.rounds.last().and_then(|last| last[0].winner)
+    }
+
+    /// Final placings once the bracket is over, champion first
+    pub fn placings(&self) -> Option<Vec<Placing>> {
+        let winner = self.winner()?;
+        let mut placings = vec![Placing { place: 1, entrant: winner }];
+        for (round, matches) in self.rounds.iter().enumerate().rev() {
+            let place = (1 << (self.rounds.len() - 1 - round)) + 1;
+            for game in matches {
+                if let Some(entrant) = game.loser() {
+                    placings.push(Placing { place, entrant });
+                }
+            }
+        }
+        Some(placings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixtures::game;
+
+    fn entrant(name: &str, qualifying: u32, handicap: u32) -> Entrant {
+        Entrant {
+            name: name.to_string(),
+            qualifying,
+            handicap,
+        }
+    }
+
+    #[test]
+    fn seeding_and_byes() {
+        let bracket = Bracket::new(vec![
+            entrant("Alice", 600, 0),
+            entrant("Bob", 650, 0),
+            entrant("Carol", 580, 0),
+        ], 1, Rolloff::OneBall);
+        assert_eq!(bracket.entrants()[0].name, "Bob");
+        assert_eq!(bracket.rounds()[0][0].entrants, (Some(0), None));
+        assert_eq!(bracket.rounds()[0][1].entrants, (Some(1), Some(2)));
+        assert_eq!(bracket.rounds()[1][0].entrants, (Some(0), None));
+        assert_eq!(bracket.pending(), vec![(0, 1)]);
+    }
+
+    #[test]
+    fn handicap_and_placings() {
+        let mut bracket = Bracket::new(vec![
+            entrant("Alice", 700, 0),
+            entrant("Bob", 650, 0),
+            entrant("Carol", 600, 0),
+            entrant("Dave", 550, 50),
+        ], 1, Rolloff::OneBall);
+        bracket.record_game(0, 0, game(4), game(3));
+        bracket.record_game(0, 1, game(3), game(4));
+        assert_eq!(bracket.rounds()[0][0].games, vec![(80, 110)]);
+        assert_eq!(bracket.rounds()[1][0].entrants, (Some(3), Some(2)));
+        bracket.record_game(1, 0, game(1), game(4));
+        assert_eq!(bracket.winner(), Some(2));
+        assert_eq!(bracket.placings(), Some(vec![
+            Placing { place: 1, entrant: 2 },
+            Placing { place: 2, entrant: 3 },
+            Placing { place: 3, entrant: 0 },
+            Placing { place: 3, entrant: 1 },
+        ]));
+    }
+
+    #[test]
+    fn ninth_and_tenth_rolloff() {
+        let mut bracket = Bracket::new(vec![
+            entrant("Alice", 700, 0),
+            entrant("Bob", 650, 0),
+        ], 1, Rolloff::NinthAndTenth);
+        bracket.record_game(0, 0, game(4), game(4));
+        assert!(bracket.needs_rolloff(0, 0));
+        assert_eq!(bracket.winner(), None);
+        bracket.record_rolloff(0, 0, &[Draw::Strike, Draw::Open(3), Draw::Open(4)],
+                               &[Draw::Strike, Draw::Strike, Draw::Open(1), Draw::Fool]);
+        assert_eq!(bracket.rounds()[0][0].rolloffs, vec![(24, 32)]);
+        assert_eq!(bracket.winner(), Some(1));
+    }
+
+    #[test]
+    fn best_of_three() {
+        let mut bracket = Bracket::new(vec![
+            entrant("Alice", 700, 0),
+            entrant("Bob", 650, 0),
+        ], 3, Rolloff::OneBall);
+        bracket.record_game(0, 0, game(4), game(3));
+        bracket.record_game(0, 0, game(2), game(3));
+        assert_eq!(bracket.winner(), None);
+        bracket.record_game(0, 0, game(4), game(1));
+        assert_eq!(bracket.winner(), Some(0));
+        assert!(bracket.pending().is_empty());
+    }
+}
@@ -0,0 +1,150 @@
+use std::fmt::{Display, Error as FmtError, Formatter};
+
+use binary;
+use Game;
+
+// Shareable game codes
+
+/// Start of every code, the version of `binary` it wraps
+pub const PREFIX: &str = "b1:";
+
+/// URL-safe base64 alphabet
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+#[derive(Debug)]
+pub enum Error {
+    /// Code not starting with `PREFIX`
+    Prefix,
+    /// Character outside the alphabet, or bits left over, at this position after the prefix
+    Character(usize),
+    /// Checksum not matching, a character was mistyped
+    Checksum,
+    /// Game that cannot be encoded, or checksum matching but the game doesn't decode
+    Game(binary::Error),
+}
+
+impl Display for Error {
+    fn fmt(&self, formatter: &mut Formatter) -> Result<(), FmtError> {
+        match *self {
+            Error::Prefix => write!(formatter, "not a game code, it should start with {}", PREFIX),
+            Error::Character(pos) => write!(formatter, "bad character at {}", pos),
+            Error::Checksum => write!(formatter, "wrong checksum, check for typos"),
+            Error::Game(ref error) => write!(formatter, "{}", error),
+        }
+    }
+}
+
+/// CRC-16/CCITT-FALSE, catches every single mistyped character
+fn checksum(bytes: &[u8]) -> u16 {
+    let mut crc: u16 = 0xffff;
+    for &byte in bytes {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x1021 } else { crc << 1 };
+        }
+    }
+    crc
+}
+
+fn to_base64(bytes: &[u8]) -> String {
+    let mut text = String::new();
+    for chunk in bytes.chunks(3) {
+        let mut buffer = [0; 3];
+        buffer[..chunk.len()].copy_from_slice(chunk);
+        let bits = (buffer[0] as u32) << 16 | (buffer[1] as u32) << 8 | buffer[2] as u32;
+        for index in 0..=chunk.len() {
+            text.push(ALPHABET[(bits >> (18 - 6 * index) & 0x3f) as usize] as char);
+        }
+    }
+    text
+}
+
+fn from_base64(text: &str) -> Result<Vec<u8>, Error> {
+    let mut bytes = Vec::new();
+    let (mut bits, mut count) = (0u32, 0);
+    for (pos, c) in text.bytes().enumerate() {
+        let value = match ALPHABET.iter().position(|&letter| letter == c) {
+            Some(value) => value as u32,
+            None => return Err(Error::Character(pos)),
+        };
+        bits = bits << 6 | value;
+        count += 6;
+        if count >= 8 {
+            count -= 8;
+            bytes.push((bits >> count) as u8);
+            bits &= (1 << count) - 1;
+        }
+    }
+    if count >= 6 || bits != 0 {
+        return Err(Error::Character(text.len()));
+    }
+    Ok(bytes)
+}
+
+/// The draws of a game as a short URL-safe code, `b1:` then base64
+///
+/// The code wraps `binary::encode` without its version byte, followed by a
+/// 16-bit checksum. A perfect game takes 18 characters. A game that `decode`
+/// would refuse is refused here too, as by `binary::encode`.
+pub fn encode(game: &Game) -> Result<String, Error> {
+    let mut bytes = binary::encode(game).map_err(Error::Game)?.split_off(1);
+    let sum = checksum(&bytes);
+    bytes.push((sum >> 8) as u8);
+    bytes.push(sum as u8);
+    Ok(format!("{}{}", PREFIX, to_base64(&bytes)))
+}
+
+/// Decode a code made by `encode`, surrounding spaces are ignored
+pub fn decode(code: &str) -> Result<Game, Error> {
+    let text = match code.trim() {
+        code if code.starts_with(PREFIX) => &code[PREFIX.len()..],
+        _ => return Err(Error::Prefix),
+    };
+    let mut bytes = from_base64(text)?;
+    if bytes.len() < 3 {
+        return Err(Error::Checksum);
+    }
+    let sum = bytes.split_off(bytes.len() - 2);
+    if checksum(&bytes) != (sum[0] as u16) << 8 | sum[1] as u16 {
+        return Err(Error::Checksum);
+    }
+    bytes.insert(0, binary::VERSION);
+    binary::decode(&bytes).map_err(Error::Game)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {Draw, Pins};
+
+    #[test]
+    fn round_trip() {
+        let perfect = encode(&Game::from_draws(&[Draw::Strike; 12])).unwrap();
+        assert_eq!(perfect.len(), 18);
+        assert!(perfect.starts_with("b1:"));
+        assert_eq!(decode(&perfect).unwrap().score(), Pins::FinalPins(300));
+        let game = Game::from_draws(&[Draw::Open(7), Draw::Spare, Draw::Split(8), Draw::Fool]);
+        let code = encode(&game).unwrap();
+        assert!(code[3..].bytes().all(|c| c.is_ascii_alphanumeric() || c == b'-' || c == b'_'));
+        let decoded = decode(&format!(" {}\n", code)).unwrap();
+        assert_eq!(binary::encode(&decoded).unwrap(), binary::encode(&game).unwrap());
+        assert_eq!(decode(&encode(&Game::new()).unwrap()).unwrap().frames().len(), 0);
+        assert!(matches!(encode(&Game::from_draws(&[Draw::Open(10)])), Err(Error::Game(binary::Error::Draw(0)))));
+        let refused = Game::from_draws(&[Draw::Open(9), Draw::Open(9)]);
+        assert!(matches!(encode(&refused), Err(Error::Game(binary::Error::Draw(1)))));
+    }
+
+    #[test]
+    fn typos_detected() {
+        let code = encode(&Game::from_draws(&[Draw::Open(3); 20])).unwrap();
+        for pos in 3..code.len() {
+            let mut typo = code.clone().into_bytes();
+            typo[pos] = if typo[pos] == b'A' { b'B' } else { b'A' };
+            let typo = String::from_utf8(typo).unwrap();
+            assert!(decode(&typo).is_err(), "{}", typo);
+        }
+        assert!(matches!(decode("x1:AAAA"), Err(Error::Prefix)));
+        assert!(matches!(decode("b1:AA*A"), Err(Error::Character(2))));
+        assert!(matches!(decode(&code[..code.len() - 1]), Err(Error::Checksum)|Err(Error::Character(_))));
+    }
+}
@@ -0,0 +1,261 @@
+use std::fmt::{Display, Error as FmtError, Formatter};
+
+use series::Series;
+use {Draw, Game, Pins};
+
+// CSV export and import
+
+/// A game on a sheet: the bowler, the game number counted from 1 for the
+/// bowler, and the game
+pub type SheetGame<'a> = (&'a str, usize, &'a Game);
+
+pub const DRAWS_HEADER: &str = "bowler,game,frame,ball,pins,mark,total";
+pub const FRAMES_HEADER: &str = "bowler,game,frame,draws,score,total";
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Error {
+    /// Line of the sheet, counted from 1 with the header
+    pub line: usize,
+    pub reason: String,
+}
+
+impl Display for Error {
+    fn fmt(&self, formatter: &mut Formatter) -> Result<(), FmtError> {
+        write!(formatter, "line {}: {}", self.line, self.reason)
+    }
+}
+
+/// Games of a series numbered in order, absentee and vacancy scores left out
+pub fn series_games<'a>(bowler: &'a str, series: &'a Series) -> Vec<SheetGame<'a>> {
+    series.entries().iter()
+        .enumerate()
+        .filter_map(|(index, entry)| entry.game().map(|game| (bowler, index + 1, game)))
+        .collect()
+}
+
+/// A field quoted when it holds a comma or a quote
+pub(crate) fn field(text: &str) -> String {
+    if text.contains(',') || text.contains('"') {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+fn mark(draw: Draw) -> &'static str {
+    match draw {
+        Draw::Open(_) => "open",
+        Draw::Split(_) => "split",
+        Draw::Spare => "spare",
+        Draw::Strike => "strike",
+        Draw::Fool => "fool",
+    }
+}
+
+/// Pins knocked down by every ball of a frame, a spare counts what the first ball left
+fn ball_pins(draws: &[Draw]) -> Vec<u32> {
+    let mut first = None;
+    draws.iter()
+        .map(|&draw| {
+            let (pins, next) = match (first, draw) {
+                (_, Draw::Strike) => (10, None),
+                (Some(first), Draw::Spare) => (10 - first, None),
+                (None, Draw::Spare) => (10, None),
+                (None, Draw::Open(x))|(None, Draw::Split(x)) => (x, Some(x)),
+                (None, Draw::Fool) => (0, Some(0)),
+                (Some(_), Draw::Open(x))|(Some(_), Draw::Split(x)) => (x, None),
+                (Some(_), Draw::Fool) => (0, None),
+            };
+            first = next;
+            pins
+        })
+        .collect()
+}
+
+/// Scoresheet total after every frame, empty once a frame waits for its bonus
+fn totals(game: &Game) -> Vec<String> {
+    let mut total = Some(0);
+    game.frame_scores().into_iter()
+        .map(|score| {
+            total = match (total, score) {
+                (Some(total), Pins::FinalPins(x)) => Some(total + x),
+                _ => None,
+            };
+            total.map(|total| total.to_string()).unwrap_or_default()
+        })
+        .collect()
+}
+
+/// One row per draw, under `DRAWS_HEADER`
+///
+/// `pins` are the pins knocked down by the ball, `mark` is `strike`, `spare`,
+/// `open`, `split` or `fool` and `total` the scoresheet total after the frame.
+pub fn draws_csv(games: &[SheetGame]) -> String {
+    let mut csv = format!("{}\n", DRAWS_HEADER);
+    for &(bowler, number, game) in games {
+        let totals = totals(game);
+        for (index, frame) in game.frames().iter().enumerate() {
+            for (ball, (&draw, pins)) in frame.draws().iter().zip(ball_pins(frame.draws())).enumerate() {
+                csv.push_str(&format!("{},{},{},{},{},{},{}\n",
+                    field(bowler), number, index + 1, ball + 1, pins, mark(draw), totals[index]));
+            }
+        }
+    }
+    csv
+}
+
+/// One row per frame, under `FRAMES_HEADER`, draws written as on a scoresheet
+/// (`X`, `/`, `-`, pins, and `S` before the pins of a split)
+pub fn frames_csv(games: &[SheetGame]) -> String {
+    let mut csv = format!("{}\n", FRAMES_HEADER);
+    for &(bowler, number, game) in games {
+        let totals = totals(game);
+        for (index, (frame, score)) in game.frames().iter().zip(game.frame_scores()).enumerate() {
+            let draws: Vec<String> = frame.draws().iter()
+                .map(|&draw| match draw {
+                    Draw::Strike => "X".to_string(),
+                    Draw::Spare => "/".to_string(),
+                    Draw::Fool => "-".to_string(),
+                    Draw::Open(x) => x.to_string(),
+                    Draw::Split(x) => format!("S{}", x),
+                })
+                .collect();
+            let score = match score {
+                Pins::FinalPins(x) => x.to_string(),
+                Pins::InprogressPins(_) => String::new(),
+            };
+            csv.push_str(&format!("{},{},{},{},{},{}\n",
+                field(bowler), number, index + 1, draws.join(" "), score, totals[index]));
+        }
+    }
+    csv
+}
+
+fn fields(line: &str) -> Option<Vec<String>> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut chars = line.chars().peekable();
+    let mut quoted = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                current.push('"');
+            },
+            '"' if quoted => quoted = false,
+            '"' if current.is_empty() => quoted = true,
+            ',' if !quoted => fields.push(::std::mem::take(&mut current)),
+            c => current.push(c),
+        }
+    }
+    if quoted {
+        return None;
+    }
+    fields.push(current);
+    Some(fields)
+}
+
+/// Load games back from rows written by `draws_csv`, maybe corrected since
+///
+/// Rows of a game must keep their order, games come back in the order they
+/// first appear. The mark decides the draw, pins are only read for `open`
+/// and `split`, and totals are computed again.
+pub fn import_draws(csv: &str) -> Result<Vec<(String, usize, Game)>, Error> {
+    let mut games: Vec<(String, usize, Game)> = Vec::new();
+    let mut lines = csv.lines().enumerate();
+    match lines.next() {
+        Some((_, header)) if header.trim_end() == DRAWS_HEADER => {},
+        _ => return Err(Error { line: 1, reason: "bad header".to_string() }),
+    }
+    for (index, line) in lines {
+        let error = |reason: &str| Error { line: index + 1, reason: reason.to_string() };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields = match fields(line.trim_end_matches('\r')) {
+            Some(fields) if fields.len() == 7 => fields,
+            _ => return Err(error("expected 7 fields")),
+        };
+        let numbers: Vec<Option<u32>> = fields[1..5].iter().map(|field| field.trim().parse().ok()).collect();
+        let (number, frame, ball) = match (numbers[0], numbers[1], numbers[2]) {
+            (Some(number), Some(frame), Some(ball)) if number > 0 => (number as usize, frame as usize, ball as usize),
+            _ => return Err(error("bad game, frame or ball")),
+        };
+        let pins = || match numbers[3] {
+            Some(pins) if pins <= 10 => Ok(pins),
+            _ => Err(error("bad pins")),
+        };
+        let draw = match fields[5].trim() {
+            "open" => Draw::Open(pins()?),
+            "split" => Draw::Split(pins()?),
+            "spare" => Draw::Spare,
+            "strike" => Draw::Strike,
+            "fool" => Draw::Fool,
+            _ => return Err(error("bad mark")),
+        };
+        let bowler = &fields[0];
+        let position = match games.iter().position(|&(ref name, game, _)| name == bowler && game == number) {
+            Some(position) => position,
+            None => {
+                games.push((bowler.clone(), number, Game::new()));
+                games.len() - 1
+            },
+        };
+        let game = &mut games[position].2;
+        if !game.accepts(draw) {
+            return Err(error("impossible draw"));
+        }
+        game.set_draw(draw);
+        if game.frames().len() != frame || game.frames()[frame - 1].draws().len() != ball {
+            return Err(error("draw out of its frame or ball"));
+        }
+    }
+    Ok(games)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_round_trip() {
+        let mut series = Series::new(2, 0);
+        series.add_game(Game::from_draws(&[Draw::Strike, Draw::Split(8), Draw::Spare, Draw::Open(3)]));
+        series.add_game(Game::from_draws(&[Draw::Strike; 12]));
+        let games = series_games("Doe, \"Big\" Jo", &series);
+        let csv = draws_csv(&games);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[1], r#""Doe, ""Big"" Jo",1,1,1,10,strike,20"#);
+        assert_eq!(lines[3], r#""Doe, ""Big"" Jo",1,2,2,2,spare,33"#);
+        assert_eq!(lines[4], r#""Doe, ""Big"" Jo",1,3,1,3,open,"#);
+        assert_eq!(lines.len(), 17);
+        let imported = import_draws(&csv).unwrap();
+        assert_eq!(imported.len(), 2);
+        assert_eq!(imported[0].0, "Doe, \"Big\" Jo");
+        assert_eq!(imported[1].2.score(), Pins::FinalPins(300));
+        assert_eq!(draws_csv(&[(&imported[0].0, 1, &imported[0].2), (&imported[1].0, 2, &imported[1].2)]), csv);
+    }
+
+    #[test]
+    fn corrected_sheet() {
+        let game = Game::from_draws(&[Draw::Open(7), Draw::Open(2), Draw::Open(9)]);
+        let csv = draws_csv(&[("Ana", 1, &game)]).replace("1,2,2,open", "1,2,3,spare");
+        let imported = import_draws(&csv).unwrap();
+        assert_eq!(imported[0].2.frame_scores()[0], Pins::FinalPins(19));
+        let bad = csv.replace("1,1,7,open", "1,1,7,strike");
+        assert_eq!(import_draws(&bad).err(), Some(Error { line: 3, reason: "impossible draw".to_string() }));
+        let moved = csv.replace("2,1,9,open", "3,1,9,open");
+        assert_eq!(import_draws(&moved).err().unwrap().reason, "draw out of its frame or ball");
+        assert!(import_draws("bowler,game\n").is_err());
+    }
+
+    #[test]
+    fn frame_rows() {
+        let game = Game::from_draws(&[Draw::Strike, Draw::Split(8), Draw::Fool, Draw::Open(4)]);
+        let csv = frames_csv(&[("Ana", 1, &game)]);
+        assert_eq!(csv, "bowler,game,frame,draws,score,total\n\
+                         Ana,1,1,X,18,18\n\
+                         Ana,1,2,S8 -,8,26\n\
+                         Ana,1,3,4,,\n");
+    }
+}
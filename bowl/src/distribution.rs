@@ -0,0 +1,188 @@
+use {Game, Pins};
+
+// Score distribution and consistency
+
+/// Sorted scores, of games or of a frame number
+#[derive(PartialEq, Eq, Debug, Default, Clone)]
+pub struct Distribution {
+    scores: Vec<u32>,
+}
+
+impl Distribution {
+    pub fn from_scores(scores: &[u32]) -> Distribution {
+        let mut scores = scores.to_vec();
+        scores.sort();
+        Distribution { scores }
+    }
+
+    pub fn add(&mut self, score: u32) {
+        let pos = match self.scores.binary_search(&score) {
+            Ok(pos) | Err(pos) => pos,
+        };
+        self.scores.insert(pos, score);
+    }
+
+    pub fn scores(&self) -> &[u32] {
+        &self.scores
+    }
+
+    pub fn count(&self) -> usize {
+        self.scores.len()
+    }
+
+    /// Scores counted by buckets of `width` pins, as `(first score, count)`,
+    /// from the bucket of the lowest score to the one of the highest, empty
+    /// buckets included
+    pub fn histogram(&self, width: u32) -> Vec<(u32, usize)> {
+        if width == 0 {
+            panic!("Shouldn't append");
+        }
+        let (low, high) = match (self.scores.first(), self.scores.last()) {
+            (Some(&low), Some(&high)) => (low / width, high / width),
+            _ => return Vec::new(),
+        };
+        (low..=high)
+            .map(|bucket| {
+                let count = self.scores.iter().filter(|&&score| score / width == bucket).count();
+                (bucket * width, count)
+            })
+            .collect()
+    }
+
+    /// Nearest-rank percentile, `p` from 0 to 100
+    pub fn percentile(&self, p: u32) -> Option<u32> {
+        if p > 100 {
+            panic!("Shouldn't append");
+        }
+        if self.scores.is_empty() {
+            return None;
+        }
+        let rank = (p as usize * self.scores.len()).div_ceil(100).max(1);
+        Some(self.scores[rank - 1])
+    }
+
+    pub fn median(&self) -> Option<u32> {
+        self.percentile(50)
+    }
+
+    pub fn mean(&self) -> f32 {
+        if self.scores.is_empty() {
+            return 0.;
+        }
+        self.scores.iter().sum::<u32>() as f32 / self.scores.len() as f32
+    }
+
+    /// Population standard deviation
+    pub fn std_dev(&self) -> f32 {
+        if self.scores.is_empty() {
+            return 0.;
+        }
+        let mean = self.mean();
+        let variance = self.scores.iter()
+            .map(|&score| (score as f32 - mean).powi(2))
+            .sum::<f32>() / self.scores.len() as f32;
+        variance.sqrt()
+    }
+
+    /// 100 minus the standard deviation as a percentage of the mean, 100 when
+    /// every score is the same, never below 0
+    pub fn consistency(&self) -> f32 {
+        let mean = self.mean();
+        if mean == 0. {
+            return 0.;
+        }
+        (100. - self.std_dev() * 100. / mean).max(0.)
+    }
+}
+
+/// Distribution of finished game scores and of every frame number
+#[derive(PartialEq, Debug, Default, Clone)]
+pub struct ScoreStats {
+    pub games: Distribution,
+    /// Frames 1 to 10, only frames with a final score
+    pub frames: [Distribution; 10],
+}
+
+impl ScoreStats {
+    pub fn from_games<'a, I: IntoIterator<Item = &'a Game>>(games: I) -> ScoreStats {
+        let mut stats = ScoreStats::default();
+        for game in games {
+            stats.add_game(game);
+        }
+        stats
+    }
+
+    pub fn add_game(&mut self, game: &Game) {
+        if let Pins::FinalPins(score) = game.score() {
+            self.games.add(score);
+        }
+        for (pos, score) in game.frame_scores().into_iter().enumerate() {
+            if let Pins::FinalPins(score) = score {
+                self.frames[pos].add(score);
+            }
+        }
+    }
+
+    /// Frame number, counted from 1, with the lowest mean score
+    pub fn worst_frame(&self) -> Option<usize> {
+        self.frame_by(|mean, other| mean < other)
+    }
+
+    /// Frame number, counted from 1, with the highest mean score
+    pub fn best_frame(&self) -> Option<usize> {
+        self.frame_by(|mean, other| mean > other)
+    }
+
+    fn frame_by<F: Fn(f32, f32) -> bool>(&self, better: F) -> Option<usize> {
+        let mut found: Option<(usize, f32)> = None;
+        for (pos, frame) in self.frames.iter().enumerate() {
+            if frame.count() == 0 {
+                continue;
+            }
+            let mean = frame.mean();
+            found = match found {
+                Some((_, other)) if !better(mean, other) => found,
+                _ => Some((pos + 1, mean)),
+            };
+        }
+        found.map(|(frame, _)| frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixtures::game;
+    use Draw;
+
+    #[test]
+    fn game_distribution() {
+        let games: Vec<Game> = (5..10).map(game).collect();
+        let stats = ScoreStats::from_games(&games);
+        assert_eq!(stats.games.scores(), &[100, 120, 140, 160, 180]);
+        assert_eq!(stats.games.median(), Some(140));
+        assert_eq!(stats.games.percentile(90), Some(180));
+        assert_eq!(stats.games.percentile(20), Some(100));
+        assert_eq!(stats.games.mean(), 140.);
+        assert!((stats.games.std_dev() - 28.284).abs() < 0.01);
+        assert!((stats.games.consistency() - 79.8).abs() < 0.1);
+        assert_eq!(stats.games.histogram(50), vec![(100, 3), (150, 2)]);
+    }
+
+    #[test]
+    fn frame_distribution() {
+        let mut draws = vec![Draw::Open(4); 20];
+        draws[12] = Draw::Open(1);
+        draws[13] = Draw::Open(1);
+        let mut games = vec![Game::from_draws(&draws)];
+        games.push(Game::from_draws(&[Draw::Open(4), Draw::Spare, Draw::Strike]));
+        let stats = ScoreStats::from_games(&games);
+        assert_eq!(stats.games.count(), 1);
+        assert_eq!(stats.frames[0].scores(), &[8, 20]);
+        assert_eq!(stats.frames[1].count(), 1);
+        assert_eq!(stats.worst_frame(), Some(7));
+        assert_eq!(stats.best_frame(), Some(1));
+        assert_eq!(Distribution::default().median(), None);
+        assert_eq!(Distribution::from_scores(&[3, 1, 2]).histogram(2), vec![(0, 1), (2, 2)]);
+    }
+}
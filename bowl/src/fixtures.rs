@@ -0,0 +1,41 @@
+use {Draw, Game};
+
+// Games for tests
+
+/// A valid finished game scoring `20 * pins`, for `pins` from 0 to 9
+///
+/// Up to 4 pins every ball is an open draw. From 5 pins every frame is a
+/// spare after `2 * pins - 10` pins, and the fill ball knocks down as many.
+pub fn game(pins: u32) -> Game {
+    match pins {
+        0..=4 => Game::from_draws(&[Draw::Open(pins); 20]),
+        5..=9 => {
+            let first = Draw::Open(2 * pins - 10);
+            let mut draws: Vec<Draw> = [first, Draw::Spare].iter().cycle().cloned().take(20).collect();
+            draws.push(first);
+            Game::from_draws(&draws)
+        },
+        _ => panic!("Shouldn't append"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Pins;
+
+    #[test]
+    fn valid_games() {
+        for pins in 0..10 {
+            let played = game(pins);
+            assert_eq!(played.score(), Pins::FinalPins(20 * pins));
+            let mut replayed = Game::new();
+            for frame in played.frames() {
+                for &draw in frame.draws() {
+                    assert!(replayed.accepts(draw), "{} pins", pins);
+                    replayed.set_draw(draw);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,318 @@
+use std::fmt::{Debug, Display, Error as FmtError, Formatter};
+
+use json::{self, Value};
+use session::Player;
+use shot::Shot;
+use {Draw, Game};
+
+// Event-sourced game history
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Event {
+    PlayerAdded(String),
+    /// A draw of a player, players are numbered from 0 in the order they were added
+    DrawRecorded(usize, Draw, Shot),
+    /// The last draw of a player taken back
+    DrawUndone(usize),
+    /// One of the player's draws, counted from 0, replaced by another
+    DrawCorrected(usize, usize, Draw),
+    /// A ball that doesn't count and is bowled again, kept for the record
+    DeadBall(usize),
+}
+
+/// An event and when it happened, in seconds from any origin the caller picks
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Stamped {
+    pub at: u64,
+    pub event: Event,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Error {
+    /// Event refused, counted from 0, it is on line `event + 1` of a log
+    pub event: usize,
+    pub reason: String,
+}
+
+impl Display for Error {
+    fn fmt(&self, formatter: &mut Formatter) -> Result<(), FmtError> {
+        write!(formatter, "event {}: {}", self.event, self.reason)
+    }
+}
+
+/// Draws and what was recorded about them, in order
+fn draws(game: &Game) -> Vec<(Draw, Shot)> {
+    game.frames().iter()
+        .flat_map(|frame| frame.draws().iter().cloned().zip(frame.shots().iter().cloned()))
+        .collect()
+}
+
+fn build(draws: &[(Draw, Shot)]) -> Option<Game> {
+    let mut game = Game::new();
+    for &(draw, shot) in draws {
+        if !game.accepts(draw) {
+            return None;
+        }
+        game.set_draw(draw);
+        *game.last_shot_mut() = shot;
+    }
+    Some(game)
+}
+
+/// Apply one event to the players, left as they were when it is refused
+///
+/// Only undoing and correcting a draw rebuild the game of the player.
+fn apply(players: &mut Vec<Player>, event: &Event) -> Result<(), &'static str> {
+    let player = match *event {
+        Event::PlayerAdded(ref name) => {
+            players.push(Player { name: name.clone(), game: Game::new() });
+            return Ok(());
+        },
+        Event::DrawRecorded(player, ..)|Event::DrawUndone(player)
+                |Event::DrawCorrected(player, ..)|Event::DeadBall(player) => player,
+    };
+    let game = match players.get_mut(player) {
+        Some(player) => &mut player.game,
+        None => return Err("unknown player"),
+    };
+    match *event {
+        Event::DrawRecorded(_, draw, shot) => {
+            if !game.accepts(draw) {
+                return Err("impossible draw");
+            }
+            game.set_draw(draw);
+            *game.last_shot_mut() = shot;
+        },
+        Event::DrawUndone(_) => {
+            let mut draws = draws(game);
+            if draws.pop().is_none() {
+                return Err("no draw to undo");
+            }
+            *game = build(&draws).expect("Shouldn't append");
+        },
+        Event::DrawCorrected(_, index, draw) => {
+            let mut draws = draws(game);
+            match draws.get_mut(index) {
+                Some(corrected) => corrected.0 = draw,
+                None => return Err("no draw to correct"),
+            }
+            *game = build(&draws).ok_or("impossible draw")?;
+        },
+        Event::PlayerAdded(_)|Event::DeadBall(_) => {},
+    }
+    Ok(())
+}
+
+/// Every change to the games of a lane, in order, nothing is ever removed
+///
+/// The games after the last event are kept up to date, the games at any
+/// earlier time are rebuilt by replaying the events.
+pub struct History {
+    events: Vec<Stamped>,
+    players: Vec<Player>,
+}
+
+impl History {
+    pub fn new() -> History {
+        History { events: Vec::new(), players: Vec::new() }
+    }
+
+    pub fn events(&self) -> &[Stamped] {
+        &self.events
+    }
+
+    /// Append an event, refused when it happens before the last one or makes
+    /// a game impossible
+    pub fn record(&mut self, at: u64, event: Event) -> Result<(), Error> {
+        let count = self.events.len();
+        let error = |reason: &str| Error { event: count, reason: reason.to_string() };
+        if self.events.last().map(|last| at < last.at).unwrap_or(false) {
+            return Err(error("event before the last one"));
+        }
+        apply(&mut self.players, &event).map_err(error)?;
+        self.events.push(Stamped { at, event });
+        Ok(())
+    }
+
+    fn replay(&self, count: usize) -> Vec<Player> {
+        let mut players = Vec::new();
+        for stamped in &self.events[..count] {
+            apply(&mut players, &stamped.event).expect("Shouldn't append");
+        }
+        players
+    }
+
+    /// Players and their games after every event
+    pub fn state(&self) -> &[Player] {
+        &self.players
+    }
+
+    /// Players and their games after the first `count` events
+    pub fn state_after(&self, count: usize) -> Vec<Player> {
+        self.replay(count.min(self.events.len()))
+    }
+
+    /// Players and their games with the events up to `at` included
+    pub fn state_at(&self, at: u64) -> Vec<Player> {
+        self.state_after(self.events.iter().take_while(|stamped| stamped.at <= at).count())
+    }
+
+    /// One JSON object per line and per event
+    ///
+    /// ```text
+    /// {"at":100,"event":"player","name":"Ana"}
+    /// {"at":130,"event":"draw","player":0,"draw":{"kind":"open","pins":7}}
+    /// {"at":150,"event":"undo","player":0}
+    /// {"at":170,"event":"correct","player":0,"index":0,"draw":{"kind":"open","pins":8}}
+    /// {"at":190,"event":"dead","player":0}
+    /// ```
+    ///
+    /// Draws are written as in `Game::to_json`, only `draw` events keep what
+    /// was recorded about the ball.
+    pub fn to_json_lines(&self) -> String {
+        let mut lines = String::new();
+        for stamped in &self.events {
+            let mut members = vec![("at".to_string(), Value::Number(stamped.at))];
+            let kind = |kind: &str| ("event".to_string(), Value::String(kind.to_string()));
+            let player = |player: usize| ("player".to_string(), Value::Number(player as u64));
+            match stamped.event {
+                Event::PlayerAdded(ref name) => {
+                    members.push(kind("player"));
+                    members.push(("name".to_string(), Value::String(name.clone())));
+                },
+                Event::DrawRecorded(who, draw, ref shot) => {
+                    members.extend(vec![kind("draw"), player(who)]);
+                    members.push(("draw".to_string(), json::draw_to_json(draw, shot)));
+                },
+                Event::DrawUndone(who) => members.extend(vec![kind("undo"), player(who)]),
+                Event::DrawCorrected(who, index, draw) => {
+                    members.extend(vec![kind("correct"), player(who)]);
+                    members.push(("index".to_string(), Value::Number(index as u64)));
+                    members.push(("draw".to_string(), json::draw_to_json(draw, &Shot::default())));
+                },
+                Event::DeadBall(who) => members.extend(vec![kind("dead"), player(who)]),
+            }
+            lines.push_str(&Value::Object(members).to_string());
+            lines.push('\n');
+        }
+        lines
+    }
+
+    /// Load a log written by `to_json_lines`, every event is checked again
+    pub fn from_json_lines(text: &str) -> Result<History, Error> {
+        let mut history = History::new();
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let error = |reason: String| Error { event: history.events.len(), reason };
+            let value = json::parse(line).map_err(|json| error(json.to_string()))?;
+            let number = |key: &str| match value.get(key).and_then(|number| number.as_u64()) {
+                Some(x) => Ok(x),
+                None => Err(error(format!("bad {}", key))),
+            };
+            let draw = || match value.get("draw") {
+                Some(draw) => json::draw_from_json(draw).map_err(|json| error(json.to_string())),
+                None => Err(error("missing draw".to_string())),
+            };
+            let at = number("at")?;
+            let event = match value.get("event").and_then(|kind| kind.as_str()) {
+                Some("player") => match value.get("name").and_then(|name| name.as_str()) {
+                    Some(name) => Event::PlayerAdded(name.to_string()),
+                    None => return Err(error("bad name".to_string())),
+                },
+                Some("draw") => {
+                    let (draw, shot) = draw()?;
+                    Event::DrawRecorded(number("player")? as usize, draw, shot)
+                },
+                Some("undo") => Event::DrawUndone(number("player")? as usize),
+                Some("correct") => Event::DrawCorrected(number("player")? as usize, number("index")? as usize, draw()?.0),
+                Some("dead") => Event::DeadBall(number("player")? as usize),
+                _ => return Err(error("bad event".to_string())),
+            };
+            history.record(at, event)?;
+        }
+        Ok(history)
+    }
+}
+
+impl Default for History {
+    fn default() -> History {
+        History::new()
+    }
+}
+
+impl Clone for History {
+    fn clone(&self) -> History {
+        History { events: self.events.clone(), players: self.replay(self.events.len()) }
+    }
+}
+
+impl PartialEq for History {
+    fn eq(&self, other: &History) -> bool {
+        self.events == other.events
+    }
+}
+
+impl Eq for History {}
+
+impl Debug for History {
+    fn fmt(&self, formatter: &mut Formatter) -> Result<(), FmtError> {
+        formatter.debug_struct("History").field("events", &self.events).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Pins;
+
+    fn history() -> History {
+        let mut history = History::new();
+        history.record(100, Event::PlayerAdded("Ana".to_string())).unwrap();
+        history.record(110, Event::PlayerAdded("Bob \"B\"".to_string())).unwrap();
+        let pocket = Shot { pocket: Some(true), ..Shot::default() };
+        history.record(130, Event::DrawRecorded(0, Draw::Strike, pocket)).unwrap();
+        history.record(150, Event::DrawRecorded(1, Draw::Open(7), Shot::default())).unwrap();
+        history.record(160, Event::DeadBall(1)).unwrap();
+        history.record(170, Event::DrawRecorded(1, Draw::Open(2), Shot::default())).unwrap();
+        history.record(180, Event::DrawUndone(1)).unwrap();
+        history.record(190, Event::DrawRecorded(1, Draw::Spare, Shot::default())).unwrap();
+        history.record(200, Event::DrawCorrected(1, 0, Draw::Open(8))).unwrap();
+        history
+    }
+
+    #[test]
+    fn replay_at_any_time() {
+        let history = history();
+        let players = history.state();
+        assert_eq!(players[0].game.score(), Pins::InprogressPins(10));
+        assert_eq!(players[0].game.frames()[0].shots()[0].pocket, Some(true));
+        assert_eq!(players[1].game.frames()[0].draws(), &[Draw::Open(8), Draw::Spare]);
+        let players = history.state_at(175);
+        assert_eq!(players[1].game.frames()[0].draws(), &[Draw::Open(7), Draw::Open(2)]);
+        assert_eq!(history.state_at(120)[1].game.frames().len(), 0);
+        assert!(history.state_at(99).is_empty());
+        assert_eq!(history.state_after(3).len(), 2);
+    }
+
+    #[test]
+    fn impossible_events_refused() {
+        let mut history = history();
+        let refused = |reason: &str| Err(Error { event: 9, reason: reason.to_string() });
+        assert_eq!(history.record(150, Event::DeadBall(0)), refused("event before the last one"));
+        assert_eq!(history.record(210, Event::DrawUndone(3)), refused("unknown player"));
+        assert_eq!(history.record(210, Event::DrawCorrected(1, 0, Draw::Strike)), refused("impossible draw"));
+        assert_eq!(history.record(210, Event::DrawCorrected(0, 1, Draw::Strike)), refused("no draw to correct"));
+        assert_eq!(history.events().len(), 9);
+        assert_eq!(history.state()[1].game.frames()[0].draws(), &[Draw::Open(8), Draw::Spare]);
+        assert_eq!(history.clone().state()[0].game.score(), Pins::InprogressPins(10));
+    }
+
+    #[test]
+    fn json_lines_round_trip() {
+        let history = history();
+        let lines = history.to_json_lines();
+        assert_eq!(lines.lines().nth(2), Some(r#"{"at":130,"event":"draw","player":0,"draw":{"kind":"strike","pocket":true}}"#));
+        assert_eq!(History::from_json_lines(&lines), Ok(history));
+        let broken = lines.replace(r#""event":"undo""#, r#""event":"redo""#);
+        assert_eq!(History::from_json_lines(&broken).err().map(|error| error.event), Some(6));
+    }
+}
@@ -0,0 +1,604 @@
+use std::fmt::{Display, Error as FmtError, Formatter};
+
+use leave::Leave;
+use oil::OilPattern;
+use shot::Shot;
+use {Draw, Game};
+
+// JSON representation of games
+
+/// Version written in every game, loading refuses any other
+pub const VERSION: u64 = 1;
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Error {
+    /// Not JSON, with the byte offset where reading stopped
+    Syntax(usize),
+    /// JSON, but not a valid game
+    Invalid(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, formatter: &mut Formatter) -> Result<(), FmtError> {
+        match *self {
+            Error::Syntax(offset) => write!(formatter, "JSON syntax error at byte {}", offset),
+            Error::Invalid(ref reason) => write!(formatter, "invalid game: {}", reason),
+        }
+    }
+}
+
+/// A JSON value, numbers are limited to non-negative integers
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(u64),
+    String(String),
+    Array(Vec<Value>),
+    /// Members in the order they were written
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match *self {
+            Value::Object(ref members) => members.iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Value::Number(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Value::Bool(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            Value::String(ref x) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match *self {
+            Value::Array(ref x) => Some(x),
+            _ => None,
+        }
+    }
+}
+
+/// Compact JSON, without spaces
+impl Display for Value {
+    fn fmt(&self, formatter: &mut Formatter) -> Result<(), FmtError> {
+        match *self {
+            Value::Null => write!(formatter, "null"),
+            Value::Bool(x) => write!(formatter, "{}", x),
+            Value::Number(x) => write!(formatter, "{}", x),
+            Value::String(ref x) => write_string(formatter, x),
+            Value::Array(ref values) => {
+                write!(formatter, "[")?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        write!(formatter, ",")?;
+                    }
+                    write!(formatter, "{}", value)?;
+                }
+                write!(formatter, "]")
+            },
+            Value::Object(ref members) => {
+                write!(formatter, "{{")?;
+                for (index, (name, value)) in members.iter().enumerate() {
+                    if index > 0 {
+                        write!(formatter, ",")?;
+                    }
+                    write_string(formatter, name)?;
+                    write!(formatter, ":{}", value)?;
+                }
+                write!(formatter, "}}")
+            },
+        }
+    }
+}
+
+fn write_string(formatter: &mut Formatter, text: &str) -> Result<(), FmtError> {
+    write!(formatter, "\"")?;
+    for c in text.chars() {
+        match c {
+            '"' => write!(formatter, "\\\"")?,
+            '\\' => write!(formatter, "\\\\")?,
+            '\n' => write!(formatter, "\\n")?,
+            '\r' => write!(formatter, "\\r")?,
+            '\t' => write!(formatter, "\\t")?,
+            c if (c as u32) < 0x20 => write!(formatter, "\\u{:04x}", c as u32)?,
+            c => write!(formatter, "{}", c)?,
+        }
+    }
+    write!(formatter, "\"")
+}
+
+// Parsing
+
+/// Arrays and objects nested deeper are refused rather than overflowing the stack
+const MAX_DEPTH: usize = 64;
+
+struct Parser<'a> {
+    text: &'a [u8],
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error<T>(&self) -> Result<T, Error> {
+        Err(Error::Syntax(self.pos))
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.pos < self.text.len() && b" \t\r\n".contains(&self.text[self.pos]) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_spaces();
+        self.text.get(self.pos).cloned()
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), Error> {
+        if self.peek() != Some(byte) {
+            return self.error();
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn keyword(&mut self, word: &str, value: Value) -> Result<Value, Error> {
+        if self.text[self.pos..].starts_with(word.as_bytes()) {
+            self.pos += word.len();
+            Ok(value)
+        } else {
+            self.error()
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, Error> {
+        if self.depth == MAX_DEPTH {
+            return self.error();
+        }
+        self.depth += 1;
+        let value = self.nested_value();
+        self.depth -= 1;
+        value
+    }
+
+    fn nested_value(&mut self) -> Result<Value, Error> {
+        match self.peek() {
+            Some(b'n') => self.keyword("null", Value::Null),
+            Some(b't') => self.keyword("true", Value::Bool(true)),
+            Some(b'f') => self.keyword("false", Value::Bool(false)),
+            Some(b'"') => self.string().map(Value::String),
+            Some(b'[') => {
+                self.pos += 1;
+                let mut values = Vec::new();
+                if self.peek() == Some(b']') {
+                    self.pos += 1;
+                    return Ok(Value::Array(values));
+                }
+                loop {
+                    values.push(self.value()?);
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b']') => {
+                            self.pos += 1;
+                            return Ok(Value::Array(values));
+                        },
+                        _ => return self.error(),
+                    }
+                }
+            },
+            Some(b'{') => {
+                self.pos += 1;
+                let mut members = Vec::new();
+                if self.peek() == Some(b'}') {
+                    self.pos += 1;
+                    return Ok(Value::Object(members));
+                }
+                loop {
+                    if self.peek() != Some(b'"') {
+                        return self.error();
+                    }
+                    let name = self.string()?;
+                    self.expect(b':')?;
+                    members.push((name, self.value()?));
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b'}') => {
+                            self.pos += 1;
+                            return Ok(Value::Object(members));
+                        },
+                        _ => return self.error(),
+                    }
+                }
+            },
+            Some(b'0'..=b'9') => {
+                let start = self.pos;
+                while self.pos < self.text.len() && self.text[self.pos].is_ascii_digit() {
+                    self.pos += 1;
+                }
+                let digits = &self.text[start..self.pos];
+                if digits.len() > 1 && digits[0] == b'0' {
+                    return Err(Error::Syntax(start));
+                }
+                match String::from_utf8_lossy(digits).parse() {
+                    Ok(x) => Ok(Value::Number(x)),
+                    Err(_) => Err(Error::Syntax(start)),
+                }
+            },
+            _ => self.error(),
+        }
+    }
+
+    fn hex(&mut self) -> Result<u32, Error> {
+        let digits = match self.text.get(self.pos..self.pos + 4) {
+            Some(digits) => String::from_utf8_lossy(digits).into_owned(),
+            None => return self.error(),
+        };
+        match u32::from_str_radix(&digits, 16) {
+            Ok(x) => {
+                self.pos += 4;
+                Ok(x)
+            },
+            Err(_) => self.error(),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        self.pos += 1;
+        let mut bytes = Vec::new();
+        loop {
+            let byte = match self.text.get(self.pos) {
+                Some(&byte) => byte,
+                None => return self.error(),
+            };
+            self.pos += 1;
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let escaped = match self.text.get(self.pos) {
+                        Some(&escaped) => escaped,
+                        None => return self.error(),
+                    };
+                    self.pos += 1;
+                    let c = match escaped {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+                            let mut code = self.hex()?;
+                            if (0xd800..0xdc00).contains(&code) {
+                                if !self.text[self.pos..].starts_with(b"\\u") {
+                                    return self.error();
+                                }
+                                self.pos += 2;
+                                let low = self.hex()?;
+                                if !(0xdc00..0xe000).contains(&low) {
+                                    return self.error();
+                                }
+                                code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                            }
+                            match ::std::char::from_u32(code) {
+                                Some(c) => c,
+                                None => return self.error(),
+                            }
+                        },
+                        _ => return self.error(),
+                    };
+                    let mut buffer = [0; 4];
+                    bytes.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+                },
+                byte if byte < 0x20 => return self.error(),
+                byte => bytes.push(byte),
+            }
+        }
+        String::from_utf8(bytes).or_else(|_| self.error())
+    }
+}
+
+pub fn parse(text: &str) -> Result<Value, Error> {
+    let mut parser = Parser { text: text.as_bytes(), pos: 0, depth: 0 };
+    let value = parser.value()?;
+    if parser.peek().is_some() {
+        return parser.error();
+    }
+    Ok(value)
+}
+
+// Games
+
+fn number(x: u64) -> Value {
+    Value::Number(x)
+}
+
+fn object(members: Vec<(&str, Value)>) -> Value {
+    Value::Object(members.into_iter().map(|(name, value)| (name.to_string(), value)).collect())
+}
+
+pub(crate) fn draw_to_json(draw: Draw, shot: &Shot) -> Value {
+    let mut members = match draw {
+        Draw::Open(x) => vec![("kind", Value::String("open".to_string())), ("pins", number(x as u64))],
+        Draw::Split(x) => vec![("kind", Value::String("split".to_string())), ("pins", number(x as u64))],
+        Draw::Spare => vec![("kind", Value::String("spare".to_string()))],
+        Draw::Strike => vec![("kind", Value::String("strike".to_string()))],
+        Draw::Fool => vec![("kind", Value::String("fool".to_string()))],
+    };
+    if let Some(leave) = shot.leave {
+        members.push(("leave", Value::Array(leave.pins().into_iter().map(|pin| number(pin as u64)).collect())));
+    }
+    if let Some(pocket) = shot.pocket {
+        members.push(("pocket", Value::Bool(pocket)));
+    }
+    if let Some(board) = shot.board {
+        members.push(("board", number(board as u64)));
+    }
+    if let Some(breakpoint) = shot.breakpoint {
+        members.push(("breakpoint", number(breakpoint as u64)));
+    }
+    if let Some(speed) = shot.speed {
+        members.push(("speed", number(speed as u64)));
+    }
+    if let Some(ball) = shot.ball {
+        members.push(("ball", number(ball as u64)));
+    }
+    object(members)
+}
+
+fn invalid<T>(reason: &str) -> Result<T, Error> {
+    Err(Error::Invalid(reason.to_string()))
+}
+
+/// Optional member, `null` counts as missing
+fn optional<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    value.get(key).filter(|&member| *member != Value::Null)
+}
+
+fn optional_number(value: &Value, key: &str, max: u64) -> Result<Option<u64>, Error> {
+    match optional(value, key) {
+        None => Ok(None),
+        Some(member) => match member.as_u64() {
+            Some(x) if x <= max => Ok(Some(x)),
+            _ => Err(Error::Invalid(format!("bad {}", key))),
+        },
+    }
+}
+
+pub(crate) fn draw_from_json(value: &Value) -> Result<(Draw, Shot), Error> {
+    let pins = || match optional_number(value, "pins", 10)? {
+        Some(x) => Ok(x as u32),
+        None => invalid("missing pins"),
+    };
+    let draw = match value.get("kind").and_then(|kind| kind.as_str()) {
+        Some("open") => Draw::Open(pins()?),
+        Some("split") => Draw::Split(pins()?),
+        Some("spare") => Draw::Spare,
+        Some("strike") => Draw::Strike,
+        Some("fool") => Draw::Fool,
+        _ => return invalid("bad draw kind"),
+    };
+    let mut shot = Shot::default();
+    if let Some(leave) = optional(value, "leave") {
+        let mut pins = Vec::new();
+        for pin in leave.as_array().unwrap_or(&[]) {
+            match pin.as_u64() {
+                Some(pin) if (1..=10).contains(&pin) => pins.push(pin as u8),
+                _ => return invalid("bad leave"),
+            }
+        }
+        if leave.as_array().is_none() {
+            return invalid("bad leave");
+        }
+        shot.leave = Some(Leave::new(&pins));
+    }
+    if let Some(pocket) = optional(value, "pocket") {
+        match pocket.as_bool() {
+            Some(pocket) => shot.pocket = Some(pocket),
+            None => return invalid("bad pocket"),
+        }
+    }
+    shot.board = optional_number(value, "board", 39)?.map(|x| x as u8);
+    shot.breakpoint = optional_number(value, "breakpoint", 39)?.map(|x| x as u8);
+    shot.speed = optional_number(value, "speed", u32::MAX as u64)?.map(|x| x as u32);
+    shot.ball = optional_number(value, "ball", u32::MAX as u64)?.map(|x| x as usize);
+    Ok((draw, shot))
+}
+
+impl Game {
+    /// The game as a JSON object
+    ///
+    /// ```text
+    /// {"version":1,"lane":12,
+    ///  "pattern":{"name":"Shark","length":44,"volume":272,"ratio":25},
+    ///  "frames":[{"draws":[{"kind":"open","pins":8,"leave":[7,10]},{"kind":"spare"}]},
+    ///            {"draws":[{"kind":"strike","pocket":true,"speed":172}]}],
+    ///  "finished":false,"score":20}
+    /// ```
+    ///
+    /// A draw is `open` or `split` with its `pins`, `spare`, `strike` or
+    /// `fool`. What was recorded about it is added when known: `leave` (pins
+    /// standing), `pocket`, `board`, `breakpoint`, `speed` and `ball`, as in
+    /// `Shot`. `lane` and `pattern` are left out when unknown. `finished` and
+    /// `score` are only written for readers, loading computes them again.
+    pub fn to_json(&self) -> String {
+        let frames = self.frames.iter()
+            .map(|frame| {
+                let draws = frame.draws().iter()
+                    .zip(frame.shots())
+                    .map(|(&draw, shot)| draw_to_json(draw, shot))
+                    .collect();
+                object(vec![("draws", Value::Array(draws))])
+            })
+            .collect();
+        let mut members = vec![("version", number(VERSION))];
+        if let Some(lane) = self.lane {
+            members.push(("lane", number(lane as u64)));
+        }
+        if let Some(ref pattern) = self.pattern {
+            members.push(("pattern", object(vec![
+                ("name", Value::String(pattern.name.clone())),
+                ("length", number(pattern.length as u64)),
+                ("volume", number(pattern.volume as u64)),
+                ("ratio", number(pattern.ratio as u64)),
+            ])));
+        }
+        members.push(("frames", Value::Array(frames)));
+        members.push(("finished", Value::Bool(self.is_finished())));
+        members.push(("score", number(self.score().value() as u64)));
+        object(members).to_string()
+    }
+
+    /// Load a game written by `to_json`, every draw is checked
+    ///
+    /// `finished` and `score` are optional, when given they must match the
+    /// loaded game.
+    pub fn from_json(text: &str) -> Result<Game, Error> {
+        let value = parse(text)?;
+        if value.get("version").and_then(|version| version.as_u64()) != Some(VERSION) {
+            return invalid("unknown version");
+        }
+        let mut game = Game::new();
+        if let Some(lane) = optional_number(&value, "lane", u32::MAX as u64)? {
+            game.set_lane(lane as u32);
+        }
+        if let Some(pattern) = optional(&value, "pattern") {
+            let name = match pattern.get("name").and_then(|name| name.as_str()) {
+                Some(name) => name,
+                None => return invalid("bad pattern name"),
+            };
+            let member = |key| match optional_number(pattern, key, u32::MAX as u64)? {
+                Some(x) => Ok(x as u32),
+                None => Err(Error::Invalid(format!("missing pattern {}", key))),
+            };
+            game.set_pattern(OilPattern::new(name, member("length")?, member("volume")?, member("ratio")?));
+        }
+        let frames = match value.get("frames").and_then(|frames| frames.as_array()) {
+            Some(frames) if frames.len() <= 10 => frames,
+            _ => return invalid("bad frames"),
+        };
+        for (index, frame) in frames.iter().enumerate() {
+            let draws = match frame.get("draws").and_then(|draws| draws.as_array()) {
+                Some(draws) if !draws.is_empty() => draws,
+                _ => return invalid("bad draws"),
+            };
+            for draw in draws {
+                let (draw, shot) = draw_from_json(draw)?;
+                if !game.accepts(draw) {
+                    return invalid("impossible draw");
+                }
+                game.set_draw(draw);
+                if game.frames.len() != index + 1 {
+                    return invalid("draws spill over the next frame");
+                }
+                *game.last_shot_mut() = shot;
+            }
+            if index + 1 < frames.len() && !game.frames[index].is_complete() {
+                return invalid("incomplete frame");
+            }
+        }
+        if let Some(finished) = optional(&value, "finished") {
+            if finished.as_bool() != Some(game.is_finished()) {
+                return invalid("finished doesn't match the draws");
+            }
+        }
+        if let Some(score) = optional(&value, "score") {
+            if score.as_u64() != Some(game.score().value() as u64) {
+                return invalid("score doesn't match the draws");
+            }
+        }
+        Ok(game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Pins;
+
+    #[test]
+    fn values_round_trip() {
+        let text = r#"{"a":[1,true,null,"x\"\\\né🎳"],"b":{}}"#;
+        let value = parse(text).unwrap();
+        assert_eq!(value.get("a").unwrap().as_array().unwrap()[3].as_str(), Some("x\"\\\né🎳"));
+        assert_eq!(parse(&value.to_string()).unwrap(), value);
+        assert_eq!(parse(" [ 1 , 2 ] ").unwrap(), Value::Array(vec![Value::Number(1), Value::Number(2)]));
+        assert_eq!(parse("[1,]"), Err(Error::Syntax(3)));
+        assert_eq!(parse("01"), Err(Error::Syntax(0)));
+        assert_eq!(parse("{} x"), Err(Error::Syntax(3)));
+        assert_eq!(parse(&"[".repeat(200000)), Err(Error::Syntax(64)));
+        assert!(parse(&format!("{}{}", "[".repeat(64), "]".repeat(64))).is_ok());
+    }
+
+    #[test]
+    fn game_round_trip() {
+        let mut game = Game::from_draws(&[Draw::Strike, Draw::Split(8)]);
+        game.set_leave(Leave::new(&[7, 10]));
+        game.set_draw(Draw::Open(1));
+        game.set_targeting(10, 7);
+        game.set_speed(172);
+        game.set_ball(2);
+        game.set_pocket(false);
+        game.set_lane(12);
+        game.set_pattern(OilPattern::new("Shark \"44\"", 44, 272, 25));
+        let json = game.to_json();
+        let loaded = Game::from_json(&json).unwrap();
+        assert_eq!(loaded.to_json(), json);
+        assert_eq!(loaded.score(), game.score());
+        assert_eq!(loaded.frames()[1].shots(), game.frames()[1].shots());
+        assert_eq!(loaded.pattern(), game.pattern());
+
+        let perfect = Game::from_draws(&[Draw::Strike; 12]);
+        let loaded = Game::from_json(&perfect.to_json()).unwrap();
+        assert_eq!(loaded.score(), Pins::FinalPins(300));
+        assert!(perfect.to_json().ends_with(r#""finished":true,"score":300}"#));
+    }
+
+    #[test]
+    fn strike_first_tenth_frames() {
+        let fools = [r#"{"draws":[{"kind":"fool"},{"kind":"fool"}]}"#; 9].join(",");
+        let game = |tenth: &str, score: u32| {
+            let json = format!(r#"{{"version":1,"frames":[{},{{"draws":[{}]}}],"finished":true,"score":{}}}"#,
+                               fools, tenth, score);
+            Game::from_json(&json).unwrap().score()
+        };
+        assert_eq!(game(r#"{"kind":"strike"},{"kind":"open","pins":3},{"kind":"spare"}"#, 20), Pins::FinalPins(20));
+        assert_eq!(game(r#"{"kind":"strike"},{"kind":"split","pins":8},{"kind":"open","pins":1}"#, 19), Pins::FinalPins(19));
+        assert_eq!(game(r#"{"kind":"strike"},{"kind":"strike"},{"kind":"open","pins":7}"#, 27), Pins::FinalPins(27));
+    }
+
+    #[test]
+    fn invalid_games() {
+        let frame = |draws: &str| format!(r#"{{"version":1,"frames":[{}]}}"#, draws);
+        assert!(Game::from_json(&frame(r#"{"draws":[{"kind":"open","pins":7},{"kind":"open","pins":5}]}"#)).is_err());
+        assert!(Game::from_json(&frame(r#"{"draws":[{"kind":"spare"}]}"#)).is_err());
+        assert!(Game::from_json(&frame(r#"{"draws":[{"kind":"strike"},{"kind":"fool"}]}"#)).is_err());
+        assert!(Game::from_json(&frame(r#"{"draws":[{"kind":"open","pins":7}]},{"draws":[{"kind":"fool"}]}"#)).is_err());
+        assert!(Game::from_json(&frame(r#"{"draws":[{"kind":"open","pins":7,"leave":[11]}]}"#)).is_err());
+        assert!(Game::from_json(r#"{"version":2,"frames":[]}"#).is_err());
+        assert!(Game::from_json(r#"{"version":1,"frames":[],"score":3}"#).is_err());
+        assert_eq!(Game::from_json(&frame(r#"{"draws":[{"kind":"open","pins":7}]}"#)).unwrap().score(),
+                   Pins::InprogressPins(7));
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::HashSet;
+
+use Game;
+
+// Lane assignment and movement
+
+/// Starting lanes and movement of bowlers or teams over a range of lanes
+///
+/// Entrants fill the lanes in order, `per_lane` on each lane. After every game
+/// the entrants of left lanes move `step` pairs to the right and those of right
+/// lanes `step` pairs to the left, wrapping around the range, so the squads
+/// meeting on a pair change every game.
+pub struct LaneAssignment {
+    first: u32,
+    pairs: u32,
+    per_lane: usize,
+    step: u32,
+}
+
+impl LaneAssignment {
+    /// Lanes from `first` to `last` included, the range must hold whole pairs
+    pub fn new(first: u32, last: u32, per_lane: usize, step: u32) -> LaneAssignment {
+        if last < first || (last - first).is_multiple_of(2) || per_lane == 0 {
+            panic!("Shouldn't append");
+        }
+        LaneAssignment {
+            first,
+            pairs: (last - first).div_ceil(2),
+            per_lane,
+            step,
+        }
+    }
+
+    fn start(&self, entrant: usize) -> u32 {
+        (entrant / self.per_lane) as u32
+    }
+
+    fn lane(&self, start: u32, game: usize) -> u32 {
+        let (pair, side) = (start / 2, start % 2);
+        let moves = (game as u32 * self.step) % self.pairs;
+        let pair = if side == 0 {
+            (pair + moves) % self.pairs
+        } else {
+            (pair + self.pairs - moves) % self.pairs
+        };
+        self.first + pair * 2 + side
+    }
+
+    pub fn starting_lanes(&self, entrants: usize) -> Vec<u32> {
+        (0..entrants).map(|entrant| self.lane(self.start(entrant), 0)).collect()
+    }
+
+    /// Lane of every entrant for every game, as `[game][entrant]`
+    ///
+    /// `None` when the entrants don't fit on the lanes, or when two squads
+    /// would meet on the same pair twice.
+    pub fn lanes(&self, entrants: usize, games: usize) -> Option<Vec<Vec<u32>>> {
+        let lanes = self.pairs * 2;
+        if entrants > lanes as usize * self.per_lane {
+            return None;
+        }
+        let used: Vec<u32> = (0..lanes).filter(|&start| (start as usize) * self.per_lane < entrants).collect();
+        let mut met = HashSet::new();
+        for game in 0..games {
+            for &left in used.iter().filter(|&&start| start % 2 == 0) {
+                let pair = (self.lane(left, game) - self.first) / 2;
+                for &right in used.iter().filter(|&&start| start % 2 == 1) {
+                    if (self.lane(right, game) - self.first) / 2 == pair && !met.insert((left, right)) {
+                        return None;
+                    }
+                }
+            }
+        }
+        Some((0..games)
+            .map(|game| (0..entrants).map(|entrant| self.lane(self.start(entrant), game)).collect())
+            .collect())
+    }
+
+    /// Empty games for every entrant, each one set on its lane, as `[entrant][game]`
+    pub fn games(&self, entrants: usize, games: usize) -> Option<Vec<Vec<Game>>> {
+        let lanes = self.lanes(entrants, games)?;
+        Some((0..entrants)
+            .map(|entrant| {
+                lanes.iter()
+                    .map(|lanes| {
+                        let mut game = Game::new();
+                        game.set_lane(lanes[entrant]);
+                        game
+                    })
+                    .collect()
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn squads_move_across_pairs() {
+        let assignment = LaneAssignment::new(11, 16, 2, 1);
+        assert_eq!(assignment.starting_lanes(5), vec![11, 11, 12, 12, 13]);
+        let lanes = assignment.lanes(12, 3).unwrap();
+        assert_eq!(lanes[1][0], 13);
+        assert_eq!(lanes[1][2], 16);
+        assert_eq!(lanes[2][0], 15);
+        assert_eq!(lanes[2][11], 12);
+        assert!(assignment.lanes(12, 4).is_none());
+    }
+
+    #[test]
+    fn no_pair_meets_twice() {
+        let assignment = LaneAssignment::new(1, 8, 1, 1);
+        assert!(assignment.lanes(8, 2).is_some());
+        // Four pairs with opposite moves bring the first squads back together on the third game
+        assert!(assignment.lanes(8, 3).is_none());
+        assert!(assignment.lanes(9, 1).is_none());
+    }
+
+    #[test]
+    fn games_carry_their_lane() {
+        let assignment = LaneAssignment::new(1, 6, 1, 1);
+        let games = assignment.games(3, 2).unwrap();
+        assert_eq!(games[1][0].lane(), Some(2));
+        assert_eq!(games[1][1].lane(), Some(6));
+        assert_eq!(games[2][1].lane(), Some(5));
+        assert!(Game::new().lane().is_none());
+    }
+}
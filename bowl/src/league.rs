@@ -0,0 +1,312 @@
+use std::cmp::Ordering;
+
+use average::{AverageRules, Handicap};
+use csv::{self, SheetGame};
+use points::PointSystem;
+use series::Series;
+use Game;
+
+// League management
+
+pub struct Bowler {
+    pub name: String,
+    /// Team the bowler is rostered on, a bowler without team is a substitute
+    pub team: Option<usize>,
+    /// Average carried over from the previous season
+    pub entering: Option<u32>,
+}
+
+pub struct Team {
+    pub name: String,
+}
+
+/// Bowlers of one team in a match, `series[i]` was bowled by `bowlers[i]`
+///
+/// A bowler rostered on another team, or on none, is a substitute: their
+/// games count for this team and for their own average. A vacant spot has no
+/// bowler and its series is made of `Entry::Vacancy` scores.
+pub struct Lineup {
+    pub team: usize,
+    pub bowlers: Vec<Option<usize>>,
+    pub series: Vec<Series>,
+}
+
+pub struct MatchResult {
+    pub home: Lineup,
+    pub away: Lineup,
+}
+
+/// Position of a team in the standings
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Standing {
+    pub team: usize,
+    pub won: f32,
+    pub lost: f32,
+    /// Scratch pinfall of the team, absentee and vacancy scores included
+    pub pinfall: u32,
+}
+
+/// Rosters and weekly results of a league
+///
+/// Averages and standings are never stored, they are rebuilt from the
+/// recorded games every time they are asked for.
+pub struct League {
+    points: PointSystem,
+    average_rules: AverageRules,
+    bowlers: Vec<Bowler>,
+    teams: Vec<Team>,
+    weeks: Vec<Vec<MatchResult>>,
+}
+
+impl League {
+    pub fn new(points: PointSystem) -> League {
+        League {
+            points,
+            average_rules: AverageRules::default(),
+            bowlers: Vec::new(),
+            teams: Vec::new(),
+            weeks: Vec::new(),
+        }
+    }
+
+    pub fn add_team(&mut self, name: &str) -> usize {
+        self.teams.push(Team { name: name.to_string() });
+        self.teams.len() - 1
+    }
+
+    pub fn add_bowler(&mut self, name: &str, team: Option<usize>) -> usize {
+        self.bowlers.push(Bowler { name: name.to_string(), team, entering: None });
+        self.bowlers.len() - 1
+    }
+
+    /// Move a bowler to another team, or out of any team
+    pub fn set_team(&mut self, bowler: usize, team: Option<usize>) {
+        self.bowlers[bowler].team = team;
+    }
+
+    pub fn set_entering_average(&mut self, bowler: usize, average: u32) {
+        self.bowlers[bowler].entering = Some(average);
+    }
+
+    pub fn set_average_rules(&mut self, rules: AverageRules) {
+        self.average_rules = rules;
+    }
+
+    pub fn bowlers(&self) -> &[Bowler] {
+        self.bowlers.as_slice()
+    }
+
+    pub fn teams(&self) -> &[Team] {
+        self.teams.as_slice()
+    }
+
+    /// Bowlers currently rostered on a team
+    pub fn roster(&self, team: usize) -> Vec<usize> {
+        (0..self.bowlers.len()).filter(|&bowler| self.bowlers[bowler].team == Some(team)).collect()
+    }
+
+    pub fn is_substitute(&self, team: usize, bowler: usize) -> bool {
+        self.bowlers[bowler].team != Some(team)
+    }
+
+    /// Record the matches of the next week, return its number (counted from 0)
+    pub fn record_week(&mut self, matches: Vec<MatchResult>) -> usize {
+        League::check_week(&matches);
+        self.weeks.push(matches);
+        self.weeks.len() - 1
+    }
+
+    /// Replace the results of an already recorded week
+    pub fn correct_week(&mut self, week: usize, matches: Vec<MatchResult>) {
+        League::check_week(&matches);
+        self.weeks[week] = matches;
+    }
+
+    fn check_week(matches: &[MatchResult]) {
+        for result in matches {
+            for lineup in &[&result.home, &result.away] {
+                if lineup.bowlers.len() != lineup.series.len() {
+                    panic!("Shouldn't append");
+                }
+            }
+        }
+    }
+
+    pub fn weeks(&self) -> &[Vec<MatchResult>] {
+        self.weeks.as_slice()
+    }
+
+    fn lineups(&self) -> impl Iterator<Item = &Lineup> {
+        self.weeks.iter()
+            .flat_map(|matches| matches.iter())
+            .flat_map(|result| vec![&result.home, &result.away])
+    }
+
+    /// Every game bowled by a bowler, in the order they were recorded
+    ///
+    /// Absentee scores recorded for the bowler are left out.
+    pub fn games(&self, bowler: usize) -> Vec<&Game> {
+        self.lineups()
+            .flat_map(|lineup| lineup.bowlers.iter().zip(lineup.series.iter()))
+            .filter(|&(&who, _)| who == Some(bowler))
+            .flat_map(|(_, series)| series.games())
+            .collect()
+    }
+
+    /// Games bowled in a week, numbered in each series, as rows for `csv`
+    ///
+    /// Vacant spots and synthetic scores are left out.
+    pub fn week_games(&self, week: usize) -> Vec<SheetGame<'_>> {
+        self.weeks[week].iter()
+            .flat_map(|result| vec![&result.home, &result.away])
+            .flat_map(|lineup| lineup.bowlers.iter().zip(lineup.series.iter()))
+            .filter_map(|(&bowler, series)| bowler.map(|bowler| (bowler, series)))
+            .flat_map(|(bowler, series)| csv::series_games(&self.bowlers[bowler].name, series))
+            .collect()
+    }
+
+    /// Running average of a bowler under the league's rules, their entering
+    /// average until they bowled enough games
+    pub fn average(&self, bowler: usize) -> Option<u32> {
+        self.average_rules
+            .average(self.games(bowler), self.bowlers[bowler].entering)
+            .map(|average| average.pins)
+    }
+
+    /// Handicap of a bowler from their current average, none without average
+    pub fn handicap(&self, bowler: usize, handicap: &Handicap) -> u32 {
+        self.average(bowler).map(|average| handicap.handicap(average)).unwrap_or(0)
+    }
+
+    /// Standings, most points won first and total pinfall breaking ties
+    pub fn standings(&self) -> Vec<Standing> {
+        let mut standings: Vec<Standing> = (0..self.teams.len())
+            .map(|team| Standing { team, won: 0., lost: 0., pinfall: 0 })
+            .collect();
+        for result in self.weeks.iter().flat_map(|matches| matches.iter()) {
+            let (home, away) = self.points
+                .score_match(&result.home.series, &result.away.series)
+                .total();
+            for &(lineup, won, lost) in &[(&result.home, home, away), (&result.away, away, home)] {
+                let standing = &mut standings[lineup.team];
+                standing.won += won;
+                standing.lost += lost;
+                standing.pinfall += lineup.series.iter()
+                    .map(|series| series.scratch().value())
+                    .sum::<u32>();
+            }
+        }
+        standings.sort_by(|a, b| {
+            b.won.partial_cmp(&a.won).unwrap_or(Ordering::Equal)
+                .then(b.pinfall.cmp(&a.pinfall))
+        });
+        standings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use average::Rounding;
+    use fixtures::game;
+    use series::Entry;
+
+    fn series(pins: &[u32]) -> Series {
+        let mut series = Series::new(pins.len(), 0);
+        for &pins in pins {
+            series.add_game(game(pins));
+        }
+        series
+    }
+
+    fn league() -> League {
+        let mut league = League::new(PointSystem::seven_points());
+        let lions = league.add_team("Lions");
+        let tigers = league.add_team("Tigers");
+        league.add_bowler("Alice", Some(lions));
+        league.add_bowler("Bob", Some(lions));
+        league.add_bowler("Carol", Some(tigers));
+        league.add_bowler("Dave", Some(tigers));
+        league.add_bowler("Eve", None);
+        league
+    }
+
+    #[test]
+    fn averages_and_standings() {
+        let mut league = league();
+        assert_eq!(league.roster(1), vec![2, 3]);
+        league.record_week(vec![MatchResult {
+            home: Lineup { team: 0, bowlers: vec![Some(0), Some(1)], series: vec![series(&[4, 4, 4]), series(&[2, 2, 3])] },
+            away: Lineup { team: 1, bowlers: vec![Some(2), Some(3)], series: vec![series(&[3, 3, 3]), series(&[3, 3, 2])] },
+        }]);
+        league.record_week(vec![MatchResult {
+            home: Lineup { team: 1, bowlers: vec![Some(2), Some(4)], series: vec![series(&[4, 4, 4]), series(&[4, 4, 4])] },
+            away: Lineup { team: 0, bowlers: vec![Some(0), Some(1)], series: vec![series(&[1, 1, 1]), series(&[2, 2, 2])] },
+        }]);
+        assert!(league.is_substitute(1, 4));
+        let games = league.week_games(1);
+        assert_eq!(games.len(), 12);
+        assert_eq!((games[3].0, games[3].1), ("Eve", 1));
+        assert!(csv::draws_csv(&games).lines().nth(1).unwrap().starts_with("Carol,1,1,1,4,open,"));
+        assert_eq!(league.average(0), Some(50));
+        assert_eq!(league.average(4), Some(80));
+        assert_eq!(league.average(3), Some(53));
+        assert_eq!(league.games(2).len(), 6);
+        assert_eq!(league.standings(), vec![
+            Standing { team: 1, won: 9., lost: 5., pinfall: 820 },
+            Standing { team: 0, won: 5., lost: 9., pinfall: 560 },
+        ]);
+    }
+
+    #[test]
+    fn corrections_rebuild_standings() {
+        let mut league = league();
+        let week = league.record_week(vec![MatchResult {
+            home: Lineup { team: 0, bowlers: vec![Some(0)], series: vec![series(&[1])] },
+            away: Lineup { team: 1, bowlers: vec![Some(2)], series: vec![series(&[2])] },
+        }]);
+        assert_eq!(league.standings()[0].team, 1);
+        league.correct_week(week, vec![MatchResult {
+            home: Lineup { team: 0, bowlers: vec![Some(0)], series: vec![series(&[3])] },
+            away: Lineup { team: 1, bowlers: vec![Some(2)], series: vec![series(&[2])] },
+        }]);
+        assert_eq!(league.standings()[0], Standing { team: 0, won: 3., lost: 0., pinfall: 60 });
+        assert_eq!(league.average(0), Some(60));
+    }
+
+    #[test]
+    fn entering_average() {
+        let mut league = league();
+        league.set_average_rules(AverageRules { rounding: Rounding::Truncate, minimum_games: 3 });
+        league.set_entering_average(0, 150);
+        league.record_week(vec![MatchResult {
+            home: Lineup { team: 0, bowlers: vec![Some(0)], series: vec![series(&[4, 4])] },
+            away: Lineup { team: 1, bowlers: vec![Some(2)], series: vec![series(&[2, 2])] },
+        }]);
+        assert_eq!(league.average(0), Some(150));
+        assert_eq!(league.average(2), None);
+        assert_eq!(league.handicap(0, &Handicap { base: 200, percentage: 80 }), 40);
+        league.record_week(vec![MatchResult {
+            home: Lineup { team: 0, bowlers: vec![Some(0)], series: vec![series(&[7])] },
+            away: Lineup { team: 1, bowlers: vec![Some(2)], series: vec![series(&[2])] },
+        }]);
+        assert_eq!(league.average(0), Some(100));
+        assert_eq!(league.average(2), Some(40));
+    }
+
+    #[test]
+    fn absentee_and_vacancy() {
+        let mut league = league();
+        let mut absent = Series::new(1, 0);
+        absent.add_entry(Entry::absentee(150, 10));
+        let mut vacant = Series::new(1, 0);
+        vacant.add_entry(Entry::Vacancy(120));
+        league.record_week(vec![MatchResult {
+            home: Lineup { team: 0, bowlers: vec![Some(0), None], series: vec![absent, vacant] },
+            away: Lineup { team: 1, bowlers: vec![Some(2)], series: vec![series(&[9])] },
+        }]);
+        assert_eq!(league.average(0), None);
+        assert!(league.games(0).is_empty());
+        assert_eq!(league.standings()[0], Standing { team: 0, won: 3., lost: 0., pinfall: 260 });
+    }
+}
@@ -0,0 +1,119 @@
+use std::fmt::{Display, Error, Formatter};
+
+// Pin-level leaves
+
+/// Pins linked diagonally to a pin of the row in front of them, a leave is a
+/// split when its pins are not all connected this way
+///
+/// Pins side by side in a row are not linked, 5-6 or 7-8 are splits.
+const NEIGHBOURS: [(u8, u8); 12] = [
+    (1, 2), (1, 3),
+    (2, 4), (2, 5), (3, 5), (3, 6),
+    (4, 7), (4, 8), (5, 8), (5, 9), (6, 9), (6, 10),
+];
+
+/// Pins left standing after a ball, numbered from 1 (head pin) to 10
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+pub struct Leave(u16);
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+pub enum LeaveGroup {
+    SinglePin,
+    /// Several pins, no split
+    MultiPin,
+    Split,
+}
+
+impl Leave {
+    pub fn new(pins: &[u8]) -> Leave {
+        let mut leave = 0;
+        for &pin in pins {
+            if !(1..=10).contains(&pin) {
+                panic!("Shouldn't append");
+            }
+            leave |= 1 << (pin - 1);
+        }
+        Leave(leave)
+    }
+
+    pub fn standing(&self, pin: u8) -> bool {
+        (1..=10).contains(&pin) && self.0 & (1 << (pin - 1)) != 0
+    }
+
+    pub fn pins(&self) -> Vec<u8> {
+        (1..=10).filter(|&pin| self.standing(pin)).collect()
+    }
+
+    pub fn count(&self) -> u32 {
+        self.0.count_ones()
+    }
+
+    /// Head pin down and the standing pins not all touching each other
+    pub fn is_split(&self) -> bool {
+        let pins = self.pins();
+        if self.standing(1) || pins.len() < 2 {
+            return false;
+        }
+        let mut connected = vec![pins[0]];
+        let mut grown = true;
+        while grown {
+            grown = false;
+            for &(a, b) in NEIGHBOURS.iter() {
+                if !self.standing(a) || !self.standing(b) {
+                    continue;
+                }
+                for &(from, to) in &[(a, b), (b, a)] {
+                    if connected.contains(&from) && !connected.contains(&to) {
+                        connected.push(to);
+                        grown = true;
+                    }
+                }
+            }
+        }
+        connected.len() < pins.len()
+    }
+
+    /// `None` for a strike, nothing is left
+    pub fn group(&self) -> Option<LeaveGroup> {
+        match self.count() {
+            0 => None,
+            1 => Some(LeaveGroup::SinglePin),
+            _ if self.is_split() => Some(LeaveGroup::Split),
+            _ => Some(LeaveGroup::MultiPin),
+        }
+    }
+}
+
+/// Pins joined with dashes, as "4-7-10"
+impl Display for Leave {
+    fn fmt(&self, formatter: &mut Formatter) -> std::result::Result<(), Error> {
+        let pins: Vec<String> = self.pins().iter().map(|pin| pin.to_string()).collect();
+        write!(formatter, "{}", pins.join("-"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leave_groups() {
+        assert_eq!(Leave::new(&[]).group(), None);
+        assert_eq!(Leave::new(&[10]).group(), Some(LeaveGroup::SinglePin));
+        assert_eq!(Leave::new(&[2, 4, 5, 8]).group(), Some(LeaveGroup::MultiPin));
+        assert_eq!(Leave::new(&[5, 8]).group(), Some(LeaveGroup::MultiPin));
+        assert_eq!(Leave::new(&[5, 6]).group(), Some(LeaveGroup::Split));
+        assert_eq!(Leave::new(&[4, 5]).group(), Some(LeaveGroup::Split));
+        assert_eq!(Leave::new(&[7, 8]).group(), Some(LeaveGroup::Split));
+        assert_eq!(Leave::new(&[1, 7]).group(), Some(LeaveGroup::MultiPin));
+        assert_eq!(Leave::new(&[7, 10]).group(), Some(LeaveGroup::Split));
+        assert_eq!(Leave::new(&[3, 10]).group(), Some(LeaveGroup::Split));
+        assert_eq!(Leave::new(&[4, 6, 7, 10]).group(), Some(LeaveGroup::Split));
+    }
+
+    #[test]
+    fn leave_display() {
+        assert_eq!(Leave::new(&[10, 4, 7]).to_string(), "4-7-10");
+        assert_eq!(Leave::new(&[10, 4, 7]).count(), 3);
+    }
+}
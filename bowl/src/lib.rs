@@ -1,5 +1,33 @@
+pub mod arsenal;
+pub mod average;
+pub mod binary;
+pub mod bracket;
+pub mod code;
+pub mod csv;
+pub mod distribution;
+#[cfg(test)]
+mod fixtures;
+pub mod history;
+pub mod json;
+pub mod lanes;
+pub mod league;
+pub mod leave;
+pub mod oil;
+pub mod patterns;
+pub mod points;
+pub mod schedule;
+pub mod series;
+pub mod session;
+pub mod shot;
+pub mod stats;
+pub mod tournament;
+
 use std::fmt::{Display, Error, Formatter};
 
+use leave::Leave;
+use oil::OilPattern;
+use shot::Shot;
+
 #[derive(PartialEq, Eq, Debug)]
 pub enum NextAction {
     NextDraw,
@@ -7,12 +35,13 @@ pub enum NextAction {
     Finish,
 }
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
 pub enum Pins {
     FinalPins(u32),
     InprogressPins(u32),
 }
 
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
 pub enum Draw {
     Open(u32),
     Split(u32),
@@ -25,31 +54,54 @@ pub enum Draw {
 
 pub trait Frame {
     // fn new() -> Self;
-    fn score(&self, Option<(usize, &Game)>) -> Pins;
+    fn score(&self, pos: Option<(usize, &Game)>) -> Pins;
     fn set_draw(&mut self, draw: Draw) -> NextAction;
     fn sum_n_draws(&self, n: usize) -> Pins;
+    fn draws(&self) -> &[Draw];
+    /// What was recorded about each draw
+    fn shots(&self) -> &[Shot];
+    /// Recorded data of the last draw
+    fn last_shot_mut(&mut self) -> &mut Shot;
+    fn is_complete(&self) -> bool;
+
+    /// Count the pins knocked down in this frame, bonus excluded, the fill
+    /// balls of the tenth frame too
+    fn pinfall(&self) -> u32 {
+        let mut pinfall = 0;
+        for draw in self.draws() {
+            match *draw {
+                Draw::Strike|Draw::Spare => return 10,
+                Draw::Open(x)|Draw::Split(x) => pinfall += x,
+                Draw::Fool => {},
+            }
+        }
+        pinfall
+    }
 }
 
 /// Represent the firts ninths frames
 pub struct RegularFrame {
     draws: Vec<Draw>,
+    shots: Vec<Shot>,
 }
 
 pub struct TenthFrame {
     draws: Vec<Draw>,
+    shots: Vec<Shot>,
 }
 
 impl RegularFrame {
     fn new() -> RegularFrame {
         RegularFrame {
             draws: Vec::with_capacity(2),
+            shots: Vec::with_capacity(2),
         }
     }
 }
 
 impl Frame for RegularFrame {
     fn set_draw(&mut self, draw: Draw) -> NextAction {
-        let next = if self.draws.len() == 0 {
+        let next = if self.draws.is_empty() {
             match draw {
                 Draw::Open(_)|Draw::Fool|Draw::Split(_) => NextAction::NextDraw,
                 Draw::Strike => NextAction::NextFrame,
@@ -62,9 +114,32 @@ impl Frame for RegularFrame {
             }
         };
         self.draws.push(draw);
+        self.shots.push(Shot::default());
         next
     }
 
+    fn draws(&self) -> &[Draw] {
+        self.draws.as_slice()
+    }
+
+    fn shots(&self) -> &[Shot] {
+        self.shots.as_slice()
+    }
+
+    fn last_shot_mut(&mut self) -> &mut Shot {
+        match self.shots.as_mut_slice().last_mut() {
+            Some(last) => last,
+            None => panic!("Shouldn't append"),
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        match self.draws.as_slice().first() {
+            Some(&Draw::Strike) => true,
+            _ => self.draws.len() == 2,
+        }
+    }
+
     fn sum_n_draws(&self, n: usize) -> Pins {
         let mut score = 0;
         if let Some(draw) = self.draws.as_slice().first() {
@@ -99,7 +174,7 @@ impl Frame for RegularFrame {
     fn score(&self, pos: Option<(usize, &Game)>) -> Pins {
         let mut score = 0;
         let mut in_progress = false;
-        if let &Some(draw) = &self.draws.as_slice().get(0) {
+        if let Some(draw) = self.draws.as_slice().first() {
             match draw {
                 &Draw::Open(x)|&Draw::Split(x) => {
                     score = x;
@@ -143,7 +218,7 @@ impl Frame for RegularFrame {
                 &Draw::Spare => panic!("Shouldn't append"),
             }
         }
-        if let &Some(draw) = &self.draws.as_slice().get(1) {
+        if let Some(draw) = self.draws.as_slice().get(1) {
             match draw {
                 &Draw::Open(x) => {
                     score += x;
@@ -180,62 +255,48 @@ impl Frame for RegularFrame {
     }
 }
 
+/// Pins knocked down by every ball, the pins are set again after a strike or a spare
+fn rack_pins(draws: &[Draw]) -> Vec<u32> {
+    let mut standing = 10;
+    draws.iter()
+        .map(|draw| {
+            let pins = match *draw {
+                Draw::Strike|Draw::Spare => standing,
+                Draw::Open(x)|Draw::Split(x) => x,
+                Draw::Fool => 0,
+            };
+            standing = match standing.saturating_sub(pins) {
+                0 => 10,
+                _ if standing != 10 => 10,
+                x => x,
+            };
+            pins
+        })
+        .collect()
+}
+
 impl TenthFrame {
     fn new() -> TenthFrame {
         TenthFrame {
             draws: Vec::with_capacity(3),
+            shots: Vec::with_capacity(3),
         }
     }
 }
 
 impl Frame for TenthFrame {
+    /// Pins knocked down by every ball, the bonus balls are part of the frame
     fn score(&self, _: Option<(usize, &Game)>) -> Pins {
-        let mut in_progress = true;
-        let mut score = 0;
-        if let Some(draw) = self.draws.as_slice().first() {
-            score += match draw {
-                &Draw::Open(x)|&Draw::Split(x) => x,
-                &Draw::Strike => 10,
-                &Draw::Fool => 0,
-                &Draw::Spare => panic!("Shouldn't append"),
-            }
-        }
-        if let Some(draw) = self.draws.as_slice().get(1) {
-            in_progress = false;
-            match draw {
-                &Draw::Open(x) => {
-                    score += x;
-                }
-                &Draw::Strike => {
-                    in_progress = true;
-                    score += 10
-                },
-                &Draw::Spare => {
-                    in_progress = true;
-                    score = 10
-                },
-                &Draw::Fool => (),
-                &Draw::Split(_) => panic!("Shouldn't append"),
-            }
-        }
-        if let Some(draw) = self.draws.as_slice().get(2) {
-            in_progress = false;
-            score += match draw {
-                &Draw::Open(x)|&Draw::Split(x) => x,
-                &Draw::Strike => 10,
-                &Draw::Fool => 0,
-                &Draw::Spare => panic!("Shouldn't append"),
-            }
-        }
-        if in_progress {
-            Pins::InprogressPins(score)
-        } else {
+        let score = rack_pins(&self.draws).iter().sum();
+        if self.is_complete() {
             Pins::FinalPins(score)
+        } else {
+            Pins::InprogressPins(score)
         }
     }
 
     fn set_draw(&mut self, draw: Draw) -> NextAction {
-        let next = if self.draws.len() == 0 {
+        let next = if self.draws.is_empty() {
             match draw {
                 Draw::Open(_)|Draw::Fool|Draw::Split(_) => NextAction::NextDraw,
                 Draw::Strike => NextAction::NextDraw,
@@ -243,6 +304,7 @@ impl Frame for TenthFrame {
             }
         } else if self.draws.len() == 1 {
             match draw {
+                _ if self.draws[0] == Draw::Strike => NextAction::NextDraw,
                 Draw::Open(_)|Draw::Fool => NextAction::Finish,
                 Draw::Strike|Draw::Spare => NextAction::NextDraw,
                 Draw::Split(_) => panic!("Shouldn't append!"),
@@ -251,40 +313,39 @@ impl Frame for TenthFrame {
             NextAction::Finish
         };
         self.draws.push(draw);
+        self.shots.push(Shot::default());
         next
     }
 
-    fn sum_n_draws(&self, n: usize) -> Pins {
-        let mut score = 0;
-        if let Some(draw) = self.draws.as_slice().first() {
-            score += match draw {
-                &Draw::Strike => 10,
-                &Draw::Open(x)|&Draw::Split(x) => x,
-                &Draw::Fool => 0,
-                &Draw::Spare => panic!("Shouldn't append"),
-            }
+    fn draws(&self) -> &[Draw] {
+        self.draws.as_slice()
+    }
+
+    fn shots(&self) -> &[Shot] {
+        self.shots.as_slice()
+    }
+
+    fn last_shot_mut(&mut self) -> &mut Shot {
+        match self.shots.as_mut_slice().last_mut() {
+            Some(last) => last,
+            None => panic!("Shouldn't append"),
         }
-        if n == 1 {
+    }
+
+    fn is_complete(&self) -> bool {
+        match self.draws.len() {
+            0 | 1 => false,
+            2 => !matches!(self.draws[..], [Draw::Strike, _]|[_, Draw::Strike]|[_, Draw::Spare]),
+            _ => true,
+        }
+    }
+
+    fn sum_n_draws(&self, n: usize) -> Pins {
+        let score = rack_pins(&self.draws).iter().take(n).sum();
+        if self.draws.len() >= n {
             Pins::FinalPins(score)
         } else {
-            if let Some(draw) = self.draws.as_slice().get(1) {
-                match draw {
-                    &Draw::Strike => {
-                        score += 10;
-                    },
-                    &Draw::Spare => {
-                        score = 10;
-                    },
-                    &Draw::Open(x) => {
-                        score += x;
-                    },
-                    &Draw::Fool => (),
-                    &Draw::Split(_) => panic!("Shouldn't append"),
-                }
-                Pins::FinalPins(score)
-            } else {
-                Pins::InprogressPins(score)
-            }
+            Pins::InprogressPins(score)
         }
     }
 }
@@ -292,19 +353,139 @@ impl Frame for TenthFrame {
 // Game
 
 pub struct Game {
-    frames: Vec<Box<Frame>>,
+    frames: Vec<Box<dyn Frame>>,
+    lane: Option<u32>,
+    pattern: Option<OilPattern>,
 }
 
 impl Game {
     pub fn new() -> Game {
         Game {
             frames: Vec::with_capacity(10), 
+            lane: None,
+            pattern: None,
+        }
+    }
+
+    /// Replay a sequence of draws into a new game
+    pub fn from_draws(draws: &[Draw]) -> Game {
+        let mut game = Game::new();
+        for draw in draws {
+            game.set_draw(*draw);
+        }
+        game
+    }
+
+    /// Record a draw, opening the next frame when the current one is complete
+    pub fn set_draw(&mut self, draw: Draw) -> NextAction {
+        let open_frame = match self.frames.as_slice().last() {
+            Some(frame) => frame.is_complete(),
+            None => true,
+        };
+        if open_frame {
+            self.next_frame();
+        }
+        self.frames.as_mut_slice().last_mut().unwrap().set_draw(draw)
+    }
+
+    /// Whether `draw` can be the next draw of the game, to check a draw
+    /// coming from outside before `set_draw`
+    pub fn accepts(&self, draw: Draw) -> bool {
+        if self.is_finished() {
+            return false;
         }
+        let draws = match self.frames.as_slice().last() {
+            Some(frame) if !frame.is_complete() => frame.draws(),
+            _ => &[],
+        };
+        // First ball of the rack being bowled, if any
+        let mut first = None;
+        for &previous in draws {
+            first = match (first, previous) {
+                (None, Draw::Strike) => None,
+                (None, previous) => Some(previous),
+                (Some(_), _) => None,
+            };
+        }
+        match (first, draw) {
+            (None, Draw::Strike)|(None, Draw::Fool) => true,
+            (None, Draw::Open(x)) => x <= 9,
+            (None, Draw::Split(x)) => x <= 8,
+            (None, Draw::Spare) => false,
+            (Some(_), Draw::Spare)|(Some(_), Draw::Fool) => true,
+            (Some(Draw::Open(x)), Draw::Open(y))|(Some(Draw::Split(x)), Draw::Open(y)) => x + y <= 9,
+            (Some(_), Draw::Open(y)) => y <= 9,
+            (Some(_), Draw::Strike)|(Some(_), Draw::Split(_)) => false,
+        }
+    }
+
+    pub fn frames(&self) -> &[Box<dyn Frame>] {
+        self.frames.as_slice()
+    }
+
+    /// Recorded data of the last draw of the game
+    pub fn last_shot_mut(&mut self) -> &mut Shot {
+        match self.frames.as_mut_slice().last_mut() {
+            Some(frame) => frame.last_shot_mut(),
+            None => panic!("Shouldn't append"),
+        }
+    }
+
+    /// Record the pins left standing after the last draw of the game
+    pub fn set_leave(&mut self, leave: Leave) {
+        self.last_shot_mut().leave = Some(leave);
+    }
+
+    /// Record whether the last draw of the game hit the pocket
+    pub fn set_pocket(&mut self, pocket: bool) {
+        self.last_shot_mut().pocket = Some(pocket);
+    }
+
+    /// Record the target board and breakpoint of the last draw of the game
+    pub fn set_targeting(&mut self, board: u8, breakpoint: u8) {
+        if !(1..=39).contains(&board) || !(1..=39).contains(&breakpoint) {
+            panic!("Shouldn't append");
+        }
+        let shot = self.last_shot_mut();
+        shot.board = Some(board);
+        shot.breakpoint = Some(breakpoint);
+    }
+
+    /// Record the speed of the last draw of the game, in tenths of a mile per hour
+    pub fn set_speed(&mut self, speed: u32) {
+        self.last_shot_mut().speed = Some(speed);
+    }
+
+    /// Record the ball used for the last draw of the game, as its index in an arsenal
+    pub fn set_ball(&mut self, ball: usize) {
+        self.last_shot_mut().ball = Some(ball);
+    }
+
+    /// Physical lane the game is bowled on, when known
+    pub fn lane(&self) -> Option<u32> {
+        self.lane
     }
 
-    pub fn next_frame(&mut self) -> &mut Box<Frame> {
+    pub fn set_lane(&mut self, lane: u32) {
+        self.lane = Some(lane);
+    }
+
+    /// Oil pattern of the lane, when known
+    pub fn pattern(&self) -> Option<&OilPattern> {
+        self.pattern.as_ref()
+    }
+
+    pub fn set_pattern(&mut self, pattern: OilPattern) {
+        self.pattern = Some(pattern);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.frames.len() == 10 && self.frames[9].is_complete()
+    }
+
+    pub fn next_frame(&mut self) -> &mut Box<dyn Frame> {
         match self.frames.len() {
-            0 ... 8 => self.frames.push(Box::new(RegularFrame::new())),
+            0..=8 => self.frames.push(Box::new(RegularFrame::new())),
             9 => self.frames.push(Box::new(TenthFrame::new())),
             _ => panic!("Shouldn't append"),
         }
@@ -312,11 +493,19 @@ impl Game {
         self.frames.as_mut_slice().last_mut().unwrap()
     }
 
+    /// Score of every frame alone, bonus included, not added up
+    pub fn frame_scores(&self) -> Vec<Pins> {
+        self.frames.iter()
+            .enumerate()
+            .map(|(pos, frame)| frame.score(Some((pos, self))))
+            .collect()
+    }
+
     pub fn score(&self) -> Pins {
         let mut score = 0;
         let mut in_progress = self.frames.len() < 10;
         for (pos, frame) in self.frames.iter().enumerate() {
-            score += match frame.score(Some((pos, &self))) {
+            score += match frame.score(Some((pos, self))) {
                 Pins::InprogressPins(x) => {
                     in_progress = true;
                     x
@@ -332,7 +521,28 @@ impl Game {
     }
 }
 
+impl Default for Game {
+    fn default() -> Game {
+        Game::new()
+    }
+}
+
 // Tools
+impl Pins {
+    pub fn value(&self) -> u32 {
+        match *self {
+            Pins::InprogressPins(x)|Pins::FinalPins(x) => x,
+        }
+    }
+
+    pub fn is_final(&self) -> bool {
+        match *self {
+            Pins::FinalPins(_) => true,
+            Pins::InprogressPins(_) => false,
+        }
+    }
+}
+
 impl Display for Pins {
     fn fmt(&self, formatter: &mut Formatter) -> std::result::Result<(), Error> {
         let score = match self {
@@ -467,7 +677,7 @@ mod tests {
         let mut game = Game::new();
         for _ in 0..9 {
             {
-            let mut frame = game.next_frame();
+            let frame = game.next_frame();
             assert_eq!(frame.set_draw(Draw::Strike), NextAction::NextFrame);
             assert_eq!(frame.score(None), Pins::InprogressPins(10));
             }
@@ -490,7 +700,7 @@ mod tests {
         let mut game = Game::new();
         for _ in 0..9 {
             {
-            let mut frame = game.next_frame();
+            let frame = game.next_frame();
             assert_eq!(frame.set_draw(Draw::Strike), NextAction::NextFrame);
             assert_eq!(frame.score(None), Pins::InprogressPins(10));
             }
@@ -513,7 +723,7 @@ mod tests {
         let mut game = Game::new();
         for _ in 0..9 {
             {
-            let mut frame = game.next_frame();
+            let frame = game.next_frame();
             assert_eq!(frame.set_draw(Draw::Strike), NextAction::NextFrame);
             assert_eq!(frame.score(None), Pins::InprogressPins(10));
             }
@@ -529,12 +739,60 @@ mod tests {
         assert_eq!(game.score(), Pins::FinalPins(258));
     }
 
+    #[test]
+    fn strike_then_open_at_end() {
+        let mut frame = TenthFrame::new();
+        assert_eq!(frame.set_draw(Draw::Strike), NextAction::NextDraw);
+        assert_eq!(frame.set_draw(Draw::Open(3)), NextAction::NextDraw);
+        assert_eq!(frame.score(None), Pins::InprogressPins(13));
+        assert_eq!(frame.set_draw(Draw::Open(4)), NextAction::Finish);
+        assert_eq!(frame.score(None), Pins::FinalPins(17));
+    }
+
+    #[test]
+    fn strike_then_spare_at_end() {
+        let mut frame = TenthFrame::new();
+        assert_eq!(frame.set_draw(Draw::Strike), NextAction::NextDraw);
+        assert_eq!(frame.set_draw(Draw::Open(3)), NextAction::NextDraw);
+        assert_eq!(frame.set_draw(Draw::Spare), NextAction::Finish);
+        assert_eq!(frame.score(None), Pins::FinalPins(20));
+        assert_eq!(frame.sum_n_draws(2), Pins::FinalPins(13));
+        let mut draws = vec![Draw::Fool; 18];
+        draws.extend_from_slice(&[Draw::Strike, Draw::Open(3), Draw::Spare]);
+        assert_eq!(Game::from_draws(&draws).score(), Pins::FinalPins(20));
+    }
+
+    #[test]
+    fn strike_then_split_at_end() {
+        let mut frame = TenthFrame::new();
+        frame.set_draw(Draw::Strike);
+        assert_eq!(frame.set_draw(Draw::Split(8)), NextAction::NextDraw);
+        assert_eq!(frame.score(None), Pins::InprogressPins(18));
+        assert_eq!(frame.set_draw(Draw::Open(1)), NextAction::Finish);
+        assert_eq!(frame.score(None), Pins::FinalPins(19));
+        let mut frame = TenthFrame::new();
+        frame.set_draw(Draw::Strike);
+        frame.set_draw(Draw::Split(8));
+        frame.set_draw(Draw::Spare);
+        assert_eq!(frame.score(None), Pins::FinalPins(20));
+    }
+
+    #[test]
+    fn two_strikes_then_open_at_end() {
+        let mut frame = TenthFrame::new();
+        frame.set_draw(Draw::Strike);
+        assert_eq!(frame.set_draw(Draw::Strike), NextAction::NextDraw);
+        assert_eq!(frame.sum_n_draws(2), Pins::FinalPins(20));
+        assert_eq!(frame.set_draw(Draw::Open(7)), NextAction::Finish);
+        assert_eq!(frame.score(None), Pins::FinalPins(27));
+    }
+
     #[test]
     fn two_open_at_end() {
         let mut game = Game::new();
         for _ in 0..8 {
             {
-            let mut frame = game.next_frame();
+            let frame = game.next_frame();
             assert_eq!(frame.set_draw(Draw::Strike), NextAction::NextFrame);
             assert_eq!(frame.score(None), Pins::InprogressPins(10));
             }
@@ -573,4 +831,23 @@ mod tests {
         }
         assert_eq!(game.score(), Pins::FinalPins(20));
     }
+
+    #[test]
+    fn accepted_draws() {
+        let mut game = Game::new();
+        assert!(!game.accepts(Draw::Spare));
+        assert!(!game.accepts(Draw::Open(10)));
+        game.set_draw(Draw::Open(7));
+        assert!(game.accepts(Draw::Open(2)));
+        assert!(!game.accepts(Draw::Open(3)));
+        assert!(!game.accepts(Draw::Strike));
+        let mut game = Game::from_draws(&[Draw::Strike; 10]);
+        assert!(game.accepts(Draw::Strike));
+        assert!(!game.accepts(Draw::Spare));
+        game.set_draw(Draw::Split(8));
+        assert!(game.accepts(Draw::Spare));
+        assert!(!game.accepts(Draw::Split(1)));
+        game.set_draw(Draw::Spare);
+        assert!(!game.accepts(Draw::Fool));
+    }
 }
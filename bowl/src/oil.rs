@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+
+use Game;
+
+// Lane oil patterns
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct OilPattern {
+    pub name: String,
+    /// Distance oiled from the foul line, in feet
+    pub length: u32,
+    /// Oil put on the lane, in tenths of a millilitre
+    pub volume: u32,
+    /// Oil in the middle of the lane for one unit on the outside, in tenths
+    pub ratio: u32,
+}
+
+impl OilPattern {
+    pub fn new(name: &str, length: u32, volume: u32, ratio: u32) -> OilPattern {
+        OilPattern {
+            name: name.to_string(),
+            length,
+            volume,
+            ratio,
+        }
+    }
+
+    /// Sport patterns have a ratio of 3 to 1 or less, house shots much more
+    pub fn is_sport(&self) -> bool {
+        self.ratio <= 30
+    }
+}
+
+/// Games grouped by the name of their oil pattern, games without one are left out
+///
+/// Every group can be given to `Stats::from_games` or `AverageRules::average`.
+pub fn by_pattern<'a, I: IntoIterator<Item = &'a Game>>(games: I) -> BTreeMap<String, Vec<&'a Game>> {
+    let mut groups: BTreeMap<String, Vec<&'a Game>> = BTreeMap::new();
+    for game in games {
+        if let Some(pattern) = game.pattern() {
+            groups.entry(pattern.name.clone()).or_default().push(game);
+        }
+    }
+    groups
+}
+
+/// Games on sport patterns, then games on house shots
+pub fn sport_and_house<'a, I: IntoIterator<Item = &'a Game>>(games: I) -> (Vec<&'a Game>, Vec<&'a Game>) {
+    let mut sport = Vec::new();
+    let mut house = Vec::new();
+    for game in games {
+        match game.pattern() {
+            Some(pattern) if pattern.is_sport() => sport.push(game),
+            Some(_) => house.push(game),
+            None => {},
+        }
+    }
+    (sport, house)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use average::AverageRules;
+    use fixtures::game;
+    use series::Series;
+    use session::Session;
+
+    #[test]
+    fn pattern_carried_to_games() {
+        let shark = OilPattern::new("Shark", 44, 272, 25);
+        let mut series = Series::new(3, 0);
+        series.add_game(game(6));
+        series.set_pattern(shark.clone());
+        series.add_game(game(7));
+        let mut house = game(9);
+        house.set_pattern(OilPattern::new("House", 40, 220, 100));
+        series.add_game(house);
+        let games = series.games();
+        assert_eq!(games[0].pattern(), Some(&shark));
+        assert_eq!(games[1].pattern(), Some(&shark));
+        assert_eq!(games[2].pattern().unwrap().name, "House");
+
+        let mut session = Session::new();
+        session.add_player("Ana");
+        session.set_pattern(shark.clone());
+        session.add_player("Bob");
+        assert!(session.players().iter().all(|player| player.game.pattern() == Some(&shark)));
+    }
+
+    #[test]
+    fn averages_by_pattern() {
+        let shark = OilPattern::new("Shark", 44, 272, 25);
+        let house = OilPattern::new("House", 40, 220, 100);
+        let mut games = Vec::new();
+        for &(pins, pattern) in &[(6, &shark), (7, &shark), (9, &house)] {
+            let mut game = game(pins);
+            game.set_pattern(pattern.clone());
+            games.push(game);
+        }
+        games.push(game(2));
+        let groups = by_pattern(&games);
+        assert_eq!(groups.keys().collect::<Vec<_>>(), vec!["House", "Shark"]);
+        let rules = AverageRules::default();
+        assert_eq!(rules.average(groups["Shark"].clone(), None).unwrap().pins, 130);
+        let (sport, house) = sport_and_house(&games);
+        assert_eq!(rules.average(sport, None).unwrap().pins, 130);
+        assert_eq!(rules.average(house, None).unwrap().pins, 180);
+    }
+}
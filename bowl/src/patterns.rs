@@ -0,0 +1,147 @@
+use std::fmt::{Display, Error, Formatter};
+
+use {Draw, Game};
+
+// Streaks and patterns
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Pattern {
+    /// Strikes in a row, three or more
+    Strikes(usize),
+    /// No open frame
+    CleanGame,
+    /// A spare in every frame
+    AllSpares,
+    /// Strikes and spares alternating in every frame, for exactly 200
+    Dutch200,
+}
+
+/// A pattern and the frames it covers, counted from 1 with both ends included
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Found {
+    pub pattern: Pattern,
+    pub frames: (usize, usize),
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum Mark {
+    Strike,
+    Spare,
+    Open,
+}
+
+/// Every streak of the game, then the whole-game patterns once it is finished
+pub fn detect(game: &Game) -> Vec<Found> {
+    let mut found = Vec::new();
+    let mut streak: Option<(usize, usize)> = None;
+    let mut end_streak = |streak: &mut Option<(usize, usize)>, last: usize| {
+        if let Some((first, count)) = streak.take() {
+            if count >= 3 {
+                found.push(Found { pattern: Pattern::Strikes(count), frames: (first, last) });
+            }
+        }
+    };
+    let mut last = 0;
+    for (index, frame) in game.frames().iter().enumerate() {
+        for draw in frame.draws() {
+            if *draw == Draw::Strike {
+                streak = match streak {
+                    Some((first, count)) => Some((first, count + 1)),
+                    None => Some((index + 1, 1)),
+                };
+                last = index + 1;
+            } else {
+                end_streak(&mut streak, last);
+            }
+        }
+    }
+    end_streak(&mut streak, last);
+    if !game.is_finished() {
+        return found;
+    }
+    let marks: Vec<Mark> = game.frames().iter()
+        .map(|frame| match frame.draws() {
+            [Draw::Strike, ..] => Mark::Strike,
+            [_, Draw::Spare, ..] => Mark::Spare,
+            _ => Mark::Open,
+        })
+        .collect();
+    let whole = |pattern| Found { pattern, frames: (1, 10) };
+    if marks.iter().all(|&mark| mark != Mark::Open) {
+        found.push(whole(Pattern::CleanGame));
+    }
+    if marks.iter().all(|&mark| mark == Mark::Spare) {
+        found.push(whole(Pattern::AllSpares));
+    }
+    if marks.windows(2).all(|pair| pair[0] != pair[1] && pair[0] != Mark::Open && pair[1] != Mark::Open)
+            && game.score().value() == 200 {
+        found.push(whole(Pattern::Dutch200));
+    }
+    found
+}
+
+/// Names bowlers use, to celebrate on the scoreboard
+impl Display for Pattern {
+    fn fmt(&self, formatter: &mut Formatter) -> std::result::Result<(), Error> {
+        match *self {
+            Pattern::Strikes(3) => write!(formatter, "Turkey"),
+            Pattern::Strikes(4) => write!(formatter, "Hambone"),
+            Pattern::Strikes(12) => write!(formatter, "Perfect game"),
+            Pattern::Strikes(x) => write!(formatter, "{}-bagger", x),
+            Pattern::CleanGame => write!(formatter, "Clean game"),
+            Pattern::AllSpares => write!(formatter, "All spares"),
+            Pattern::Dutch200 => write!(formatter, "Dutch 200"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn streaks() {
+        let mut draws = vec![Draw::Strike, Draw::Strike, Draw::Strike, Draw::Open(8), Draw::Spare];
+        draws.extend_from_slice(&[Draw::Strike; 4]);
+        draws.extend_from_slice(&[Draw::Open(3), Draw::Open(4)]);
+        let game = Game::from_draws(&draws);
+        assert_eq!(detect(&game), vec![
+            Found { pattern: Pattern::Strikes(3), frames: (1, 3) },
+            Found { pattern: Pattern::Strikes(4), frames: (5, 8) },
+        ]);
+        assert_eq!(Pattern::Strikes(4).to_string(), "Hambone");
+        assert_eq!(Pattern::Strikes(6).to_string(), "6-bagger");
+    }
+
+    #[test]
+    fn perfect_game() {
+        let game = Game::from_draws(&[Draw::Strike; 12]);
+        assert_eq!(detect(&game), vec![
+            Found { pattern: Pattern::Strikes(12), frames: (1, 10) },
+            Found { pattern: Pattern::CleanGame, frames: (1, 10) },
+        ]);
+    }
+
+    #[test]
+    fn dutch_200() {
+        let mut draws = Vec::new();
+        for _ in 0..5 {
+            draws.extend_from_slice(&[Draw::Strike, Draw::Open(5), Draw::Spare]);
+        }
+        draws.push(Draw::Strike);
+        let game = Game::from_draws(&draws);
+        assert_eq!(game.score().value(), 200);
+        assert_eq!(detect(&game), vec![
+            Found { pattern: Pattern::CleanGame, frames: (1, 10) },
+            Found { pattern: Pattern::Dutch200, frames: (1, 10) },
+        ]);
+    }
+
+    #[test]
+    fn all_spares() {
+        let draws: Vec<Draw> = [Draw::Open(9), Draw::Spare].iter().cycle().cloned().take(21).collect();
+        let found = detect(&Game::from_draws(&draws));
+        assert!(found.contains(&Found { pattern: Pattern::AllSpares, frames: (1, 10) }));
+        assert!(found.contains(&Found { pattern: Pattern::CleanGame, frames: (1, 10) }));
+    }
+}
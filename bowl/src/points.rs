@@ -0,0 +1,199 @@
+use series::Series;
+
+/// Points awarded in a team match, every contest is bowled with handicap
+///
+/// The winner of a contest takes its points, a tie splits them.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct PointSystem {
+    /// Points for winning one team game
+    pub per_game: u32,
+    /// Points for the best total series pinfall
+    pub per_series: u32,
+    /// Points for each individual head-to-head game, bowlers matched by position
+    pub per_matchup: u32,
+}
+
+/// Pinfall of both teams in one contest and the points it gave
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Contest {
+    pub pinfall: (u32, u32),
+    pub points: (f32, f32),
+}
+
+/// Point breakdown of a team match
+#[derive(PartialEq, Debug)]
+pub struct MatchPoints {
+    pub games: Vec<Contest>,
+    pub series: Option<Contest>,
+    /// Head-to-head contests, by game then by bowler position
+    pub matchups: Vec<Vec<Contest>>,
+}
+
+impl PointSystem {
+    /// One point per game won and two points for the series total
+    pub fn two_one() -> PointSystem {
+        PointSystem {
+            per_game: 1,
+            per_series: 2,
+            per_matchup: 0,
+        }
+    }
+
+    /// Two points per game won and one point for the series total, seven points on three games
+    pub fn seven_points() -> PointSystem {
+        PointSystem {
+            per_game: 2,
+            per_series: 1,
+            per_matchup: 0,
+        }
+    }
+
+    /// Four points per game won, nothing for the series total
+    pub fn four_per_game() -> PointSystem {
+        PointSystem {
+            per_game: 4,
+            per_series: 0,
+            per_matchup: 0,
+        }
+    }
+
+    /// Score a match between two teams, each one given as the series of its bowlers
+    pub fn score_match(&self, home: &[Series], away: &[Series]) -> MatchPoints {
+        let games = home.iter().chain(away.iter())
+            .map(|series| series.length())
+            .max()
+            .unwrap_or(0);
+        let mut points = MatchPoints {
+            games: Vec::with_capacity(games),
+            series: None,
+            matchups: Vec::new(),
+        };
+        for game in 0..games {
+            if self.per_game > 0 {
+                let pinfall = (team_game(home, game), team_game(away, game));
+                points.games.push(Contest::new(pinfall, self.per_game));
+            }
+            if self.per_matchup > 0 {
+                points.matchups.push(home.iter().zip(away.iter())
+                    .map(|(home, away)| {
+                        let pinfall = (bowler_game(home, game), bowler_game(away, game));
+                        Contest::new(pinfall, self.per_matchup)
+                    })
+                    .collect());
+            }
+        }
+        if self.per_series > 0 {
+            let pinfall = (team_series(home), team_series(away));
+            points.series = Some(Contest::new(pinfall, self.per_series));
+        }
+        points
+    }
+}
+
+fn bowler_game(series: &Series, game: usize) -> u32 {
+    series.handicap_score(game).unwrap_or(0)
+}
+
+fn team_game(team: &[Series], game: usize) -> u32 {
+    team.iter().map(|series| bowler_game(series, game)).sum()
+}
+
+fn team_series(team: &[Series]) -> u32 {
+    team.iter().map(|series| series.handicap_total().value()).sum()
+}
+
+impl Contest {
+    fn new(pinfall: (u32, u32), points: u32) -> Contest {
+        let points = points as f32;
+        let points = if pinfall.0 > pinfall.1 {
+            (points, 0.)
+        } else if pinfall.0 < pinfall.1 {
+            (0., points)
+        } else {
+            (points / 2., points / 2.)
+        };
+        Contest { pinfall, points }
+    }
+
+    pub fn is_tie(&self) -> bool {
+        self.pinfall.0 == self.pinfall.1
+    }
+}
+
+impl MatchPoints {
+    fn contests(&self) -> Vec<&Contest> {
+        self.games.iter()
+            .chain(self.series.iter())
+            .chain(self.matchups.iter().flat_map(|game| game.iter()))
+            .collect()
+    }
+
+    /// Points won by each team over the whole match
+    pub fn total(&self) -> (f32, f32) {
+        self.contests().iter()
+            .fold((0., 0.), |total, contest| (total.0 + contest.points.0, total.1 + contest.points.1))
+    }
+
+    /// Number of contests that ended in a tie
+    pub fn ties(&self) -> usize {
+        self.contests().iter().filter(|contest| contest.is_tie()).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixtures::game;
+
+    fn series(handicap: u32, pins: &[u32]) -> Series {
+        let mut series = Series::new(pins.len(), handicap);
+        for &pins in pins {
+            series.add_game(game(pins));
+        }
+        series
+    }
+
+    #[test]
+    fn seven_points_match() {
+        let home = [series(10, &[3, 4, 2]), series(0, &[1, 1, 1])];
+        let away = [series(0, &[2, 3, 3]), series(20, &[1, 2, 1])];
+        let points = PointSystem::seven_points().score_match(&home, &away);
+        assert_eq!(points.games.len(), 3);
+        assert_eq!(points.games[0].pinfall, (90, 80));
+        assert_eq!(points.games[0].points, (2., 0.));
+        assert_eq!(points.games[1].pinfall, (110, 120));
+        assert_eq!(points.games[1].points, (0., 2.));
+        assert_eq!(points.games[2].pinfall, (70, 100));
+        assert_eq!(points.series, Some(Contest { pinfall: (270, 300), points: (0., 1.) }));
+        assert!(points.matchups.is_empty());
+        assert_eq!(points.total(), (2., 5.));
+        assert_eq!(points.ties(), 0);
+    }
+
+    #[test]
+    fn tie_splits_points() {
+        let home = [series(10, &[2])];
+        let away = [series(30, &[1])];
+        let points = PointSystem::two_one().score_match(&home, &away);
+        assert_eq!(points.games[0].points, (0.5, 0.5));
+        assert_eq!(points.total(), (1.5, 1.5));
+        assert_eq!(points.ties(), 2);
+    }
+
+    #[test]
+    fn head_to_head_points() {
+        let system = PointSystem {
+            per_game: 2,
+            per_series: 0,
+            per_matchup: 1,
+        };
+        let home = [series(0, &[4, 2]), series(0, &[1, 1])];
+        let away = [series(0, &[3, 3]), series(0, &[1, 2])];
+        let points = system.score_match(&home, &away);
+        assert_eq!(points.series, None);
+        assert_eq!(points.matchups[0][0].points, (1., 0.));
+        assert!(points.matchups[0][1].is_tie());
+        assert_eq!(points.matchups[1][1].points, (0., 1.));
+        assert_eq!(points.total(), (3.5, 4.5));
+    }
+}
@@ -0,0 +1,170 @@
+use session::{LanePair, PairSession};
+
+// League round-robin schedules
+
+/// One side of a scheduled match
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Slot {
+    Team(usize),
+    /// Team holding a standings position (1 is the leader) when the week is bowled
+    Position(usize),
+    /// Blind or vacant opponent, the other team bowls for its own points
+    Vacant,
+}
+
+/// Two slots meeting on a lane pair
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Pairing {
+    pub home: Slot,
+    pub away: Slot,
+    pub pair: LanePair,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum WeekKind {
+    RoundRobin,
+    /// Teams meet by standings position: 1 vs 2, 3 vs 4...
+    PositionRound,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Week {
+    pub kind: WeekKind,
+    pub pairings: Vec<Pairing>,
+}
+
+impl Week {
+    /// Empty sessions for the week, ready for the bowlers of both teams
+    pub fn sessions(&self) -> Vec<PairSession> {
+        self.pairings.iter().map(|pairing| PairSession::new(pairing.pair)).collect()
+    }
+}
+
+/// A season made of two halves, each half is a full round robin
+///
+/// The second half swaps the home and away teams of the first one. With an odd
+/// number of teams one team meets the vacant slot every week.
+pub struct Schedule {
+    weeks: Vec<Week>,
+}
+
+impl Schedule {
+    /// Schedule `teams` teams on the lane pairs, with a position round closing each half if asked
+    pub fn new(teams: usize, pairs: &[LanePair], position_rounds: bool) -> Schedule {
+        let slots = teams + teams % 2;
+        if teams < 2 || pairs.len() < slots / 2 {
+            panic!("Shouldn't append");
+        }
+        let slot = |team: usize| if team < teams { Slot::Team(team) } else { Slot::Vacant };
+        let mut weeks = Vec::new();
+        for half in 0..2 {
+            // Circle method: the first slot stays, the others turn around it
+            let mut circle: Vec<usize> = (0..slots).collect();
+            for round in 0..slots - 1 {
+                let mut matches = Vec::with_capacity(slots / 2);
+                for index in 0..slots / 2 {
+                    let (mut home, mut away) = (circle[index], circle[slots - 1 - index]);
+                    if (index == 0 && round % 2 == 1) != (half == 1) {
+                        std::mem::swap(&mut home, &mut away);
+                    }
+                    matches.push((slot(home), slot(away)));
+                }
+                weeks.push(Week {
+                    kind: WeekKind::RoundRobin,
+                    pairings: assign_pairs(matches, pairs, weeks.len()),
+                });
+                let last = circle.pop().unwrap();
+                circle.insert(1, last);
+            }
+            if position_rounds {
+                let matches = (0..slots / 2)
+                    .map(|index| {
+                        let away = if index * 2 + 1 < teams { Slot::Position(index * 2 + 2) } else { Slot::Vacant };
+                        (Slot::Position(index * 2 + 1), away)
+                    })
+                    .collect();
+                weeks.push(Week {
+                    kind: WeekKind::PositionRound,
+                    pairings: assign_pairs(matches, pairs, weeks.len()),
+                });
+            }
+        }
+        Schedule { weeks }
+    }
+
+    pub fn weeks(&self) -> &[Week] {
+        self.weeks.as_slice()
+    }
+}
+
+/// Lane pairs move up one pair every week
+fn assign_pairs(matches: Vec<(Slot, Slot)>, pairs: &[LanePair], week: usize) -> Vec<Pairing> {
+    matches.into_iter()
+        .enumerate()
+        .map(|(index, (home, away))| Pairing {
+            home,
+            away,
+            pair: pairs[(index + week) % pairs.len()],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(count: u32) -> Vec<LanePair> {
+        (0..count).map(|pair| LanePair { left: pair * 2 + 1, right: pair * 2 + 2 }).collect()
+    }
+
+    fn meets(week: &Week, a: Slot, b: Slot) -> bool {
+        week.pairings.iter().any(|pairing| {
+            (pairing.home == a && pairing.away == b) || (pairing.home == b && pairing.away == a)
+        })
+    }
+
+    #[test]
+    fn every_team_meets_once_per_half() {
+        let schedule = Schedule::new(6, &pairs(3), false);
+        assert_eq!(schedule.weeks().len(), 10);
+        for half in schedule.weeks().chunks(5) {
+            for a in 0..6 {
+                for b in a + 1..6 {
+                    let count = half.iter()
+                        .filter(|week| meets(week, Slot::Team(a), Slot::Team(b)))
+                        .count();
+                    assert_eq!(count, 1);
+                }
+            }
+        }
+        let first = schedule.weeks()[0].pairings[1];
+        let second = schedule.weeks()[5].pairings[1];
+        assert_eq!((first.home, first.away), (second.away, second.home));
+    }
+
+    #[test]
+    fn odd_teams_get_vacant_weeks() {
+        let schedule = Schedule::new(5, &pairs(3), false);
+        assert_eq!(schedule.weeks().len(), 10);
+        for team in 0..5 {
+            let byes = schedule.weeks()[..5].iter()
+                .filter(|week| meets(week, Slot::Team(team), Slot::Vacant))
+                .count();
+            assert_eq!(byes, 1);
+        }
+    }
+
+    #[test]
+    fn pairs_rotate_and_position_rounds() {
+        let schedule = Schedule::new(3, &pairs(2), true);
+        assert_eq!(schedule.weeks().len(), 8);
+        assert_eq!(schedule.weeks()[0].pairings[0].pair, LanePair { left: 1, right: 2 });
+        assert_eq!(schedule.weeks()[1].pairings[0].pair, LanePair { left: 3, right: 4 });
+        let position = &schedule.weeks()[3];
+        assert_eq!(position.kind, WeekKind::PositionRound);
+        assert_eq!((position.pairings[0].home, position.pairings[0].away), (Slot::Position(1), Slot::Position(2)));
+        assert_eq!((position.pairings[1].home, position.pairings[1].away), (Slot::Position(3), Slot::Vacant));
+        assert_eq!(position.sessions().len(), 2);
+        assert_eq!(schedule.weeks()[7].kind, WeekKind::PositionRound);
+    }
+}
@@ -0,0 +1,227 @@
+use oil::OilPattern;
+use {Game, Pins};
+
+/// One game of a series, bowled or replaced by a synthetic score
+///
+/// Synthetic scores count toward series and team totals, never toward the
+/// bowler's own statistics.
+pub enum Entry {
+    Bowled(Game),
+    /// Absent bowler: their average minus the league penalty
+    Absentee(u32),
+    /// Vacant roster spot: the league's fixed score, handicap included
+    Vacancy(u32),
+}
+
+impl Entry {
+    pub fn absentee(average: u32, penalty: u32) -> Entry {
+        Entry::Absentee(average.saturating_sub(penalty))
+    }
+
+    pub fn score(&self) -> Pins {
+        match *self {
+            Entry::Bowled(ref game) => game.score(),
+            Entry::Absentee(x)|Entry::Vacancy(x) => Pins::FinalPins(x),
+        }
+    }
+
+    pub fn game(&self) -> Option<&Game> {
+        match *self {
+            Entry::Bowled(ref game) => Some(game),
+            Entry::Absentee(_)|Entry::Vacancy(_) => None,
+        }
+    }
+
+    pub fn is_synthetic(&self) -> bool {
+        self.game().is_none()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.game().map(|game| game.is_finished()).unwrap_or(true)
+    }
+}
+
+/// Games bowled in a row by one bowler, usually three or four
+///
+/// The series is in progress until `length` games are bowled and finished.
+pub struct Series {
+    entries: Vec<Entry>,
+    length: usize,
+    handicap: u32,
+    pattern: Option<OilPattern>,
+}
+
+impl Series {
+    /// `handicap` is the bowler's handicap for one game
+    pub fn new(length: usize, handicap: u32) -> Series {
+        Series {
+            entries: Vec::with_capacity(length),
+            length,
+            handicap,
+            pattern: None,
+        }
+    }
+
+    pub fn next_game(&mut self) -> &mut Game {
+        self.add_entry(Entry::Bowled(Game::new()));
+        match self.entries.as_mut_slice().last_mut() {
+            Some(&mut Entry::Bowled(ref mut game)) => game,
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn add_game(&mut self, game: Game) {
+        self.add_entry(Entry::Bowled(game));
+    }
+
+    /// Add a game or a synthetic score, a bowled game without oil pattern
+    /// gets the one of the series
+    pub fn add_entry(&mut self, mut entry: Entry) {
+        if self.entries.len() >= self.length {
+            panic!("Shouldn't append");
+        }
+        if let (&mut Entry::Bowled(ref mut game), Some(pattern)) = (&mut entry, self.pattern.as_ref()) {
+            if game.pattern().is_none() {
+                game.set_pattern(pattern.clone());
+            }
+        }
+        self.entries.push(entry);
+    }
+
+    pub fn pattern(&self) -> Option<&OilPattern> {
+        self.pattern.as_ref()
+    }
+
+    /// Oil pattern of the series, also set on the games bowled without one
+    pub fn set_pattern(&mut self, pattern: OilPattern) {
+        for entry in &mut self.entries {
+            if let Entry::Bowled(ref mut game) = *entry {
+                if game.pattern().is_none() {
+                    game.set_pattern(pattern.clone());
+                }
+            }
+        }
+        self.pattern = Some(pattern);
+    }
+
+    /// Every game of the series, synthetic scores included
+    pub fn entries(&self) -> &[Entry] {
+        self.entries.as_slice()
+    }
+
+    /// Games actually bowled, the only ones for individual statistics
+    pub fn games(&self) -> Vec<&Game> {
+        self.entries.iter().filter_map(|entry| entry.game()).collect()
+    }
+
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    pub fn handicap(&self) -> u32 {
+        self.handicap
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.entries.len() == self.length && self.entries.iter().all(|entry| entry.is_finished())
+    }
+
+    fn pins(&self, score: u32) -> Pins {
+        if self.is_finished() {
+            Pins::FinalPins(score)
+        } else {
+            Pins::InprogressPins(score)
+        }
+    }
+
+    /// Score of one entry with handicap, vacancies already include theirs
+    pub fn handicap_score(&self, entry: usize) -> Option<u32> {
+        self.entries.get(entry).map(|entry| match *entry {
+            Entry::Vacancy(x) => x,
+            _ => entry.score().value() + self.handicap,
+        })
+    }
+
+    /// Total pinfall of the series without handicap, synthetic scores included
+    ///
+    /// Both count toward team totals, a vacancy with its fixed score as is.
+    pub fn scratch(&self) -> Pins {
+        self.pins(self.entries.iter().map(|entry| entry.score().value()).sum())
+    }
+
+    /// Total of the series with the handicap of every game
+    pub fn handicap_total(&self) -> Pins {
+        self.pins((0..self.entries.len()).filter_map(|entry| self.handicap_score(entry)).sum())
+    }
+
+    /// Best game bowled in the series, `None` before the first game
+    pub fn high_game(&self) -> Option<Pins> {
+        self.games().iter()
+            .map(|game| game.score())
+            .max_by_key(|score| score.value())
+    }
+
+    /// Scratch average per game bowled, fractions dropped
+    pub fn average(&self) -> Pins {
+        let games = self.games();
+        if games.is_empty() {
+            return Pins::InprogressPins(0);
+        }
+        let total: u32 = games.iter().map(|game| game.score().value()).sum();
+        self.pins(total / games.len() as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Draw;
+
+    fn strikes(n: usize) -> Game {
+        Game::from_draws(&vec![Draw::Strike; n])
+    }
+
+    #[test]
+    fn three_games_series() {
+        let mut series = Series::new(3, 12);
+        series.add_game(Game::from_draws(&[Draw::Open(1); 20]));
+        series.add_game(strikes(12));
+        series.add_game(Game::from_draws(&[Draw::Open(4); 20]));
+        assert!(series.is_finished());
+        assert_eq!(series.scratch(), Pins::FinalPins(400));
+        assert_eq!(series.handicap_total(), Pins::FinalPins(436));
+        assert_eq!(series.high_game(), Some(Pins::FinalPins(300)));
+        assert_eq!(series.average(), Pins::FinalPins(133));
+    }
+
+    #[test]
+    fn in_progress_series() {
+        let mut series = Series::new(3, 10);
+        assert_eq!(series.high_game(), None);
+        assert_eq!(series.average(), Pins::InprogressPins(0));
+        series.add_game(strikes(12));
+        {
+            let game = series.next_game();
+            game.set_draw(Draw::Strike);
+        }
+        assert!(!series.is_finished());
+        assert_eq!(series.scratch(), Pins::InprogressPins(310));
+        assert_eq!(series.handicap_total(), Pins::InprogressPins(330));
+        assert_eq!(series.high_game(), Some(Pins::FinalPins(300)));
+        assert_eq!(series.average(), Pins::InprogressPins(155));
+    }
+
+    #[test]
+    fn synthetic_scores() {
+        let mut series = Series::new(3, 10);
+        series.add_game(Game::from_draws(&[Draw::Open(4); 20]));
+        series.add_entry(Entry::absentee(150, 10));
+        series.add_entry(Entry::Vacancy(120));
+        assert!(series.is_finished());
+        assert_eq!(series.scratch(), Pins::FinalPins(340));
+        assert_eq!(series.handicap_total(), Pins::FinalPins(360));
+        assert_eq!(series.games().len(), 1);
+        assert_eq!(series.high_game(), Some(Pins::FinalPins(80)));
+        assert_eq!(series.average(), Pins::FinalPins(80));
+    }
+}
@@ -0,0 +1,327 @@
+use oil::OilPattern;
+use {Draw, Game, NextAction};
+
+// Multi-bowler play
+
+/// A bowler and the game they are bowling
+pub struct Player {
+    pub name: String,
+    pub game: Game,
+}
+
+/// Several bowlers sharing a lane, each one bowls a full frame in turn
+pub struct Session {
+    players: Vec<Player>,
+    current: usize,
+    pattern: Option<OilPattern>,
+}
+
+impl Session {
+    pub fn new() -> Session {
+        Session {
+            players: Vec::new(),
+            current: 0,
+            pattern: None,
+        }
+    }
+
+    /// Add a bowler at the end of the order of play, return their position
+    pub fn add_player(&mut self, name: &str) -> usize {
+        let mut game = Game::new();
+        if let Some(ref pattern) = self.pattern {
+            game.set_pattern(pattern.clone());
+        }
+        self.players.push(Player {
+            name: name.to_string(),
+            game,
+        });
+        self.players.len() - 1
+    }
+
+    pub fn players(&self) -> &[Player] {
+        self.players.as_slice()
+    }
+
+    pub fn pattern(&self) -> Option<&OilPattern> {
+        self.pattern.as_ref()
+    }
+
+    /// Oil pattern of the lane, set on the game of every bowler
+    pub fn set_pattern(&mut self, pattern: OilPattern) {
+        for player in &mut self.players {
+            player.game.set_pattern(pattern.clone());
+        }
+        self.pattern = Some(pattern);
+    }
+
+    /// Position of the bowler on the approach, `None` once everybody finished
+    pub fn current_player(&self) -> Option<usize> {
+        if self.is_finished() {
+            None
+        } else {
+            Some(self.current)
+        }
+    }
+
+    /// Record a draw for the current bowler
+    ///
+    /// `NextFrame` means the next bowler is up, `Finish` that every game is over.
+    pub fn set_draw(&mut self, draw: Draw) -> NextAction {
+        let player = match self.current_player() {
+            Some(player) => player,
+            None => panic!("Shouldn't append"),
+        };
+        match self.players[player].game.set_draw(draw) {
+            NextAction::NextDraw => NextAction::NextDraw,
+            NextAction::NextFrame|NextAction::Finish => {
+                if self.is_finished() {
+                    return NextAction::Finish;
+                }
+                loop {
+                    self.current = (self.current + 1) % self.players.len();
+                    if !self.players[self.current].game.is_finished() {
+                        break;
+                    }
+                }
+                NextAction::NextFrame
+            },
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.players.iter().all(|player| player.game.is_finished())
+    }
+}
+
+impl Default for Session {
+    fn default() -> Session {
+        Session::new()
+    }
+}
+
+// Cross-lane pair play
+
+/// The two physical lanes of a pair
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct LanePair {
+    pub left: u32,
+    pub right: u32,
+}
+
+/// A frame bowled during pair play, in order of play
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Turn {
+    pub team: usize,
+    pub player: usize,
+    pub frame: usize,
+    pub lane: u32,
+}
+
+/// Marks and pinfall of the frames bowled on one lane
+#[derive(PartialEq, Eq, Debug, Default, Clone, Copy)]
+pub struct LaneStats {
+    pub frames: u32,
+    pub strikes: u32,
+    pub spares: u32,
+    pub opens: u32,
+    pub pinfall: u32,
+}
+
+impl LaneStats {
+    /// Pins knocked down per frame, bonus excluded
+    pub fn average_pinfall(&self) -> f32 {
+        if self.frames == 0 {
+            0.
+        } else {
+            self.pinfall as f32 / self.frames as f32
+        }
+    }
+}
+
+/// American format: two teams alternating between the lanes of a pair every frame
+///
+/// The first team starts on the left lane, the second on the right one, then
+/// the bowlers of both teams take turns, one frame each.
+pub struct PairSession {
+    pair: LanePair,
+    teams: [Session; 2],
+    next_team: usize,
+    turns: Vec<Turn>,
+}
+
+impl PairSession {
+    pub fn new(pair: LanePair) -> PairSession {
+        PairSession {
+            pair,
+            teams: [Session::new(), Session::new()],
+            next_team: 0,
+            turns: Vec::new(),
+        }
+    }
+
+    pub fn pair(&self) -> LanePair {
+        self.pair
+    }
+
+    pub fn add_player(&mut self, team: usize, name: &str) -> usize {
+        self.teams[team].add_player(name)
+    }
+
+    pub fn team(&self, team: usize) -> &Session {
+        &self.teams[team]
+    }
+
+    /// Every frame bowled so far, in order of play across both lanes
+    pub fn turns(&self) -> &[Turn] {
+        self.turns.as_slice()
+    }
+
+    /// Lane a team bowls the given frame on (frames are counted from 0)
+    pub fn lane_for(&self, team: usize, frame: usize) -> u32 {
+        if (team + frame).is_multiple_of(2) {
+            self.pair.left
+        } else {
+            self.pair.right
+        }
+    }
+
+    /// Record a draw for the bowler on the approach
+    ///
+    /// `NextFrame` means the turn goes to the other lane, `Finish` that both
+    /// teams are done.
+    pub fn set_draw(&mut self, draw: Draw) -> NextAction {
+        let team = self.next_team;
+        let player = match self.teams[team].current_player() {
+            Some(player) => player,
+            None => panic!("Shouldn't append"),
+        };
+        let frames = self.teams[team].players()[player].game.frames();
+        let starts_frame = match frames.last() {
+            Some(frame) => frame.is_complete(),
+            None => true,
+        };
+        if starts_frame {
+            let frame = frames.len();
+            let lane = self.lane_for(team, frame);
+            self.turns.push(Turn { team, player, frame, lane });
+        }
+        match self.teams[team].set_draw(draw) {
+            NextAction::NextDraw => NextAction::NextDraw,
+            NextAction::NextFrame|NextAction::Finish => {
+                if self.is_finished() {
+                    return NextAction::Finish;
+                }
+                if !self.teams[1 - team].is_finished() {
+                    self.next_team = 1 - team;
+                }
+                NextAction::NextFrame
+            },
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.teams.iter().all(|team| team.is_finished())
+    }
+
+    /// Statistics of the frames bowled on one lane of the pair
+    pub fn lane_stats(&self, lane: u32) -> LaneStats {
+        let mut stats = LaneStats::default();
+        for turn in self.turns.iter().filter(|turn| turn.lane == lane) {
+            let frame = &self.teams[turn.team].players()[turn.player].game.frames()[turn.frame];
+            stats.frames += 1;
+            stats.pinfall += frame.pinfall();
+            match frame.draws() {
+                [Draw::Strike, ..] => stats.strikes += 1,
+                [_, Draw::Spare, ..] => stats.spares += 1,
+                [_, _, ..] => stats.opens += 1,
+                _ => {},
+            }
+        }
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Pins;
+
+    #[test]
+    fn players_take_turns() {
+        let mut session = Session::new();
+        session.add_player("Alice");
+        session.add_player("Bob");
+        assert_eq!(session.current_player(), Some(0));
+        assert_eq!(session.set_draw(Draw::Strike), NextAction::NextFrame);
+        assert_eq!(session.current_player(), Some(1));
+        assert_eq!(session.set_draw(Draw::Open(3)), NextAction::NextDraw);
+        assert_eq!(session.set_draw(Draw::Open(4)), NextAction::NextFrame);
+        assert_eq!(session.current_player(), Some(0));
+        assert_eq!(session.players()[0].game.score(), Pins::InprogressPins(10));
+        assert_eq!(session.players()[1].game.score(), Pins::InprogressPins(7));
+    }
+
+    #[test]
+    fn session_finish() {
+        let mut session = Session::new();
+        session.add_player("Alice");
+        session.add_player("Bob");
+        let mut last = NextAction::NextDraw;
+        for _ in 0..40 {
+            last = session.set_draw(Draw::Open(1));
+        }
+        assert_eq!(last, NextAction::Finish);
+        assert!(session.is_finished());
+        assert_eq!(session.current_player(), None);
+        assert_eq!(session.players()[1].game.score(), Pins::FinalPins(20));
+    }
+
+    #[test]
+    fn teams_alternate_lanes() {
+        let mut session = PairSession::new(LanePair { left: 7, right: 8 });
+        session.add_player(0, "Alice");
+        session.add_player(1, "Bob");
+        assert_eq!(session.set_draw(Draw::Strike), NextAction::NextFrame);
+        assert_eq!(session.set_draw(Draw::Open(4)), NextAction::NextDraw);
+        assert_eq!(session.set_draw(Draw::Spare), NextAction::NextFrame);
+        assert_eq!(session.set_draw(Draw::Open(9)), NextAction::NextDraw);
+        assert_eq!(session.set_draw(Draw::Fool), NextAction::NextFrame);
+        assert_eq!(session.turns(), &[
+            Turn { team: 0, player: 0, frame: 0, lane: 7 },
+            Turn { team: 1, player: 0, frame: 0, lane: 8 },
+            Turn { team: 0, player: 0, frame: 1, lane: 8 },
+        ]);
+        assert_eq!(session.lane_stats(7), LaneStats {
+            frames: 1,
+            strikes: 1,
+            spares: 0,
+            opens: 0,
+            pinfall: 10,
+        });
+        assert_eq!(session.lane_stats(8), LaneStats {
+            frames: 2,
+            strikes: 0,
+            spares: 1,
+            opens: 1,
+            pinfall: 19,
+        });
+    }
+
+    #[test]
+    fn pair_session_finish() {
+        let mut session = PairSession::new(LanePair { left: 1, right: 2 });
+        session.add_player(0, "Alice");
+        session.add_player(1, "Bob");
+        session.add_player(1, "Carol");
+        let mut last = NextAction::NextDraw;
+        while !session.is_finished() {
+            last = session.set_draw(Draw::Strike);
+        }
+        assert_eq!(last, NextAction::Finish);
+        assert_eq!(session.turns().len(), 30);
+        assert_eq!(session.lane_stats(1).frames + session.lane_stats(2).frames, 30);
+        // Fill balls of the tenth frames are left out
+        assert_eq!(session.lane_stats(1).pinfall + session.lane_stats(2).pinfall, 300);
+        assert_eq!(session.team(1).players()[1].game.score(), Pins::FinalPins(300));
+    }
+}
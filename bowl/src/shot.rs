@@ -0,0 +1,18 @@
+use leave::Leave;
+
+/// What was recorded about a draw besides its pins, every field is optional
+#[derive(PartialEq, Eq, Debug, Default, Clone, Copy)]
+pub struct Shot {
+    /// Pins left standing after the ball
+    pub leave: Option<Leave>,
+    /// Whether the ball hit the pocket
+    pub pocket: Option<bool>,
+    /// Board crossed at the arrows, counted from 1 on the right edge
+    pub board: Option<u8>,
+    /// Farthest board reached before the ball hooks back
+    pub breakpoint: Option<u8>,
+    /// Ball speed in tenths of a mile per hour
+    pub speed: Option<u32>,
+    /// Ball used, as its index in the bowler's arsenal
+    pub ball: Option<usize>,
+}
@@ -0,0 +1,525 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use leave::{Leave, LeaveGroup};
+use series::Series;
+use shot::Shot;
+use {Draw, Game};
+
+// Bowler statistics
+
+/// Marks of one or many games, every frame is counted on its first rack
+#[derive(PartialEq, Eq, Debug, Default, Clone, Copy)]
+pub struct Stats {
+    pub games: u32,
+    /// Complete frames
+    pub frames: u32,
+    pub strikes: u32,
+    pub spares: u32,
+    pub opens: u32,
+    /// Frames leaving one pin standing after the first ball
+    pub single_pins: u32,
+    pub single_pins_converted: u32,
+    /// Balls bowled right after a spare in the tenth frame
+    pub fill_balls: u32,
+    pub fill_pins: u32,
+}
+
+fn percentage(count: u32, total: u32) -> f32 {
+    if total == 0 {
+        0.
+    } else {
+        count as f32 * 100. / total as f32
+    }
+}
+
+fn pins(draw: Draw) -> u32 {
+    match draw {
+        Draw::Strike => 10,
+        Draw::Open(x)|Draw::Split(x) => x,
+        Draw::Spare|Draw::Fool => 0,
+    }
+}
+
+impl Stats {
+    /// Statistics of every frame of the games
+    pub fn from_games<'a, I: IntoIterator<Item = &'a Game>>(games: I) -> Stats {
+        let mut stats = Stats::default();
+        for game in games {
+            stats.add_game(game);
+        }
+        stats
+    }
+
+    pub fn add_game(&mut self, game: &Game) {
+        self.games += 1;
+        for (index, frame) in game.frames().iter().enumerate() {
+            let draws = frame.draws();
+            match *draws {
+                [Draw::Strike, ..] => self.strikes += 1,
+                [first, second, ..] => {
+                    if second == Draw::Spare {
+                        self.spares += 1;
+                    } else {
+                        self.opens += 1;
+                    }
+                    if pins(first) == 9 {
+                        self.single_pins += 1;
+                        if second == Draw::Spare {
+                            self.single_pins_converted += 1;
+                        }
+                    }
+                },
+                _ => continue,
+            }
+            self.frames += 1;
+            if index == 9 {
+                if let [_, Draw::Spare, fill] = *draws {
+                    self.fill_balls += 1;
+                    self.fill_pins += pins(fill);
+                }
+            }
+        }
+    }
+
+    pub fn strike_percentage(&self) -> f32 {
+        percentage(self.strikes, self.frames)
+    }
+
+    pub fn spare_percentage(&self) -> f32 {
+        percentage(self.spares, self.frames)
+    }
+
+    pub fn open_percentage(&self) -> f32 {
+        percentage(self.opens, self.frames)
+    }
+
+    pub fn single_pin_conversion(&self) -> f32 {
+        percentage(self.single_pins_converted, self.single_pins)
+    }
+
+    /// Average pins knocked down by a fill ball
+    pub fn fill_ball_average(&self) -> f32 {
+        if self.fill_balls == 0 {
+            0.
+        } else {
+            self.fill_pins as f32 / self.fill_balls as f32
+        }
+    }
+}
+
+/// First ball and spare attempt of every rack of a frame that wasn't a strike,
+/// as indexes in its draws
+pub(crate) fn spare_attempts(draws: &[Draw]) -> Vec<(usize, usize)> {
+    let mut attempts = Vec::new();
+    let mut index = 0;
+    while index < draws.len() {
+        if draws[index] == Draw::Strike {
+            index += 1;
+        } else if index + 1 < draws.len() {
+            attempts.push((index, index + 1));
+            index += 2;
+        } else {
+            break;
+        }
+    }
+    attempts
+}
+
+/// First ball of every rack of a frame, as indexes in its draws
+pub(crate) fn first_balls(draws: &[Draw]) -> Vec<usize> {
+    let mut firsts = Vec::new();
+    let mut index = 0;
+    while index < draws.len() {
+        firsts.push(index);
+        index += if draws[index] == Draw::Strike { 1 } else { 2 };
+    }
+    firsts
+}
+
+// First ball
+
+/// Pins knocked down by the first ball of every rack, tenth frame fill balls included
+#[derive(PartialEq, Eq, Debug, Default, Clone, Copy)]
+pub struct FirstBall {
+    pub balls: u32,
+    pub pins: u32,
+    /// Number of first balls for every pin count, from 0 to 10
+    pub distribution: [u32; 11],
+    /// First balls recorded as pocket hits
+    pub pocket_hits: u32,
+    /// Pocket hits carrying for a strike
+    pub pocket_strikes: u32,
+}
+
+impl FirstBall {
+    pub fn from_games<'a, I: IntoIterator<Item = &'a Game>>(games: I) -> FirstBall {
+        let mut stats = FirstBall::default();
+        for game in games {
+            stats.add_game(game);
+        }
+        stats
+    }
+
+    pub fn add_game(&mut self, game: &Game) {
+        for frame in game.frames() {
+            for first in first_balls(frame.draws()) {
+                let draw = frame.draws()[first];
+                let pins = pins(draw);
+                self.balls += 1;
+                self.pins += pins;
+                self.distribution[pins as usize] += 1;
+                if frame.shots()[first].pocket == Some(true) {
+                    self.pocket_hits += 1;
+                    if draw == Draw::Strike {
+                        self.pocket_strikes += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn average(&self) -> f32 {
+        if self.balls == 0 {
+            0.
+        } else {
+            self.pins as f32 / self.balls as f32
+        }
+    }
+
+    /// Share of pocket hits that struck, only pocket hits recorded as such count
+    pub fn carry_percentage(&self) -> f32 {
+        percentage(self.pocket_strikes, self.pocket_hits)
+    }
+}
+
+// Spare conversion by leave
+
+#[derive(PartialEq, Eq, Debug, Default, Clone, Copy)]
+pub struct Conversion {
+    pub attempts: u32,
+    pub converted: u32,
+}
+
+impl Conversion {
+    pub(crate) fn add(&mut self, converted: bool) {
+        self.attempts += 1;
+        if converted {
+            self.converted += 1;
+        }
+    }
+
+    pub fn percentage(&self) -> f32 {
+        percentage(self.converted, self.attempts)
+    }
+}
+
+/// Spare conversion for every leave recorded on a first ball
+pub struct LeaveStats {
+    leaves: BTreeMap<Leave, Conversion>,
+}
+
+impl LeaveStats {
+    pub fn from_games<'a, I: IntoIterator<Item = &'a Game>>(games: I) -> LeaveStats {
+        let mut stats = LeaveStats { leaves: BTreeMap::new() };
+        for game in games {
+            stats.add_game(game);
+        }
+        stats
+    }
+
+    /// Spare attempts without a recorded leave are skipped
+    pub fn add_game(&mut self, game: &Game) {
+        for frame in game.frames() {
+            for (first, attempt) in spare_attempts(frame.draws()) {
+                if let Some(leave) = frame.shots()[first].leave {
+                    self.leaves.entry(leave)
+                        .or_default()
+                        .add(frame.draws()[attempt] == Draw::Spare);
+                }
+            }
+        }
+    }
+
+    pub fn leave(&self, leave: Leave) -> Conversion {
+        self.leaves.get(&leave).cloned().unwrap_or_default()
+    }
+
+    /// Every leave faced, with its conversion
+    pub fn leaves(&self) -> Vec<(Leave, Conversion)> {
+        self.leaves.iter().map(|(&leave, &conversion)| (leave, conversion)).collect()
+    }
+
+    /// Conversion of all the leaves of a group
+    pub fn group(&self, group: LeaveGroup) -> Conversion {
+        self.leaves.iter()
+            .filter(|&(leave, _)| leave.group() == Some(group))
+            .fold(Conversion::default(), |total, (_, conversion)| Conversion {
+                attempts: total.attempts + conversion.attempts,
+                converted: total.converted + conversion.converted,
+            })
+    }
+
+    /// Leaves from the worst converted to the best, most faced first on a tie
+    pub fn weakest(&self) -> Vec<(Leave, Conversion)> {
+        let mut leaves = self.leaves();
+        leaves.sort_by(|a, b| {
+            (a.1.converted * b.1.attempts).cmp(&(b.1.converted * a.1.attempts))
+                .then(b.1.attempts.cmp(&a.1.attempts))
+        });
+        leaves
+    }
+}
+
+// Splits
+
+/// Splits left by the first ball and their conversion
+///
+/// A first ball is a split when drawn as `Draw::Split` or when its recorded
+/// leave is one. Only splits followed by a spare attempt are counted.
+#[derive(PartialEq, Eq, Debug, Default, Clone)]
+pub struct SplitStats {
+    pub splits: Conversion,
+    leaves: BTreeMap<Leave, Conversion>,
+}
+
+impl SplitStats {
+    pub fn from_games<'a, I: IntoIterator<Item = &'a Game>>(games: I) -> SplitStats {
+        let mut stats = SplitStats::default();
+        for game in games {
+            stats.add_game(game);
+        }
+        stats
+    }
+
+    /// Splits of every game of the series, over a season or many
+    pub fn from_series<'a, I: IntoIterator<Item = &'a Series>>(series: I) -> SplitStats {
+        let mut stats = SplitStats::default();
+        for series in series {
+            for game in series.games() {
+                stats.add_game(game);
+            }
+        }
+        stats
+    }
+
+    pub fn add_game(&mut self, game: &Game) {
+        for frame in game.frames() {
+            for (first, attempt) in spare_attempts(frame.draws()) {
+                let leave = frame.shots()[first].leave;
+                let split = match frame.draws()[first] {
+                    Draw::Split(_) => true,
+                    _ => leave.map(|leave| leave.is_split()).unwrap_or(false),
+                };
+                if !split {
+                    continue;
+                }
+                let converted = frame.draws()[attempt] == Draw::Spare;
+                self.splits.add(converted);
+                if let Some(leave) = leave {
+                    self.leaves.entry(leave).or_default().add(converted);
+                }
+            }
+        }
+    }
+
+    pub fn leave(&self, leave: Leave) -> Conversion {
+        self.leaves.get(&leave).cloned().unwrap_or_default()
+    }
+
+    /// Splits with a recorded leave, most often left first
+    pub fn most_frequent(&self) -> Vec<(Leave, Conversion)> {
+        let mut leaves: Vec<(Leave, Conversion)> = self.leaves.iter()
+            .map(|(&leave, &conversion)| (leave, conversion))
+            .collect();
+        leaves.sort_by_key(|&(_, conversion)| Reverse(conversion.attempts));
+        leaves
+    }
+}
+
+// Ball tracking
+
+/// First balls and strikes, as attempts and conversions, grouped by a key
+/// taken from what was recorded about every first ball
+///
+/// Balls for which `key` gives `None` are left out, so
+/// `strikes_by(&games, |shot| shot.speed.map(|speed| speed / 10))` gives the
+/// strike percentage for every whole mile per hour.
+pub fn strikes_by<'a, I, K, F>(games: I, key: F) -> BTreeMap<K, Conversion>
+        where I: IntoIterator<Item = &'a Game>, K: Ord, F: Fn(&Shot) -> Option<K> {
+    let mut groups = BTreeMap::new();
+    for game in games {
+        for frame in game.frames() {
+            for first in first_balls(frame.draws()) {
+                if let Some(key) = key(&frame.shots()[first]) {
+                    let conversion: &mut Conversion = groups.entry(key).or_default();
+                    conversion.add(frame.draws()[first] == Draw::Strike);
+                }
+            }
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marks_of_one_game() {
+        let game = Game::from_draws(&[
+            Draw::Strike,
+            Draw::Open(9), Draw::Spare,
+            Draw::Open(9), Draw::Open(0),
+            Draw::Open(7), Draw::Spare,
+            Draw::Strike,
+            Draw::Fool, Draw::Open(3),
+            Draw::Strike,
+            Draw::Open(9), Draw::Spare,
+            Draw::Split(8), Draw::Open(1),
+            Draw::Open(6), Draw::Spare, Draw::Open(8),
+        ]);
+        let stats = Stats::from_games(vec![&game]);
+        assert_eq!(stats.frames, 10);
+        assert_eq!(stats.strikes, 3);
+        assert_eq!(stats.spares, 4);
+        assert_eq!(stats.opens, 3);
+        assert_eq!(stats.strike_percentage(), 30.);
+        assert_eq!(stats.single_pins, 3);
+        assert_eq!(stats.single_pins_converted, 2);
+        assert_eq!(stats.fill_balls, 1);
+        assert_eq!(stats.fill_ball_average(), 8.);
+    }
+
+    #[test]
+    fn many_games() {
+        let perfect = Game::from_draws(&[Draw::Strike; 12]);
+        let spares = Game::from_draws(&[Draw::Open(9), Draw::Spare].iter().cycle().cloned().take(21).collect::<Vec<_>>());
+        let stats = Stats::from_games(vec![&perfect, &spares]);
+        assert_eq!(stats.games, 2);
+        assert_eq!(stats.frames, 20);
+        assert_eq!(stats.strike_percentage(), 50.);
+        assert_eq!(stats.spare_percentage(), 50.);
+        assert_eq!(stats.open_percentage(), 0.);
+        assert_eq!(stats.single_pin_conversion(), 100.);
+        assert_eq!(stats.fill_ball_average(), 9.);
+        assert_eq!(Stats::default().strike_percentage(), 0.);
+    }
+
+    #[test]
+    fn first_ball_average() {
+        let mut game = Game::new();
+        for &(first, pocket) in &[
+            (Draw::Strike, Some(true)),
+            (Draw::Open(9), Some(true)),
+            (Draw::Strike, None),
+            (Draw::Open(8), Some(false)),
+        ] {
+            game.set_draw(first);
+            if let Some(pocket) = pocket {
+                game.set_pocket(pocket);
+            }
+            if first != Draw::Strike {
+                game.set_draw(Draw::Open(0));
+            }
+        }
+        for _ in 0..5 {
+            game.set_draw(Draw::Fool);
+            game.set_draw(Draw::Fool);
+        }
+        game.set_draw(Draw::Open(7));
+        game.set_draw(Draw::Spare);
+        game.set_draw(Draw::Strike);
+        let stats = FirstBall::from_games(vec![&game]);
+        assert_eq!(stats.balls, 11);
+        assert_eq!(stats.pins, 54);
+        assert_eq!(stats.distribution[10], 3);
+        assert_eq!(stats.distribution[0], 5);
+        assert_eq!(stats.distribution[9], 1);
+        assert_eq!(stats.pocket_hits, 2);
+        assert_eq!(stats.carry_percentage(), 50.);
+        assert_eq!(FirstBall::default().average(), 0.);
+    }
+
+    #[test]
+    fn conversion_by_leave() {
+        let mut game = Game::new();
+        for &(first, leave, second) in &[
+            (Draw::Open(9), Leave::new(&[10]), Draw::Spare),
+            (Draw::Open(9), Leave::new(&[10]), Draw::Fool),
+            (Draw::Open(9), Leave::new(&[7]), Draw::Spare),
+            (Draw::Split(8), Leave::new(&[7, 10]), Draw::Fool),
+            (Draw::Open(7), Leave::new(&[2, 4, 5]), Draw::Spare),
+        ] {
+            game.set_draw(first);
+            game.set_leave(leave);
+            game.set_draw(second);
+        }
+        game.set_draw(Draw::Open(9));
+        game.set_draw(Draw::Spare);
+        let stats = LeaveStats::from_games(vec![&game]);
+        assert_eq!(stats.leave(Leave::new(&[10])), Conversion { attempts: 2, converted: 1 });
+        assert_eq!(stats.leave(Leave::new(&[10])).percentage(), 50.);
+        assert_eq!(stats.leave(Leave::new(&[1])).attempts, 0);
+        assert_eq!(stats.group(LeaveGroup::SinglePin), Conversion { attempts: 3, converted: 2 });
+        assert_eq!(stats.group(LeaveGroup::Split), Conversion { attempts: 1, converted: 0 });
+        assert_eq!(stats.group(LeaveGroup::MultiPin), Conversion { attempts: 1, converted: 1 });
+        assert_eq!(stats.weakest()[0].0, Leave::new(&[7, 10]));
+        assert_eq!(stats.weakest()[1].0, Leave::new(&[10]));
+        assert_eq!(stats.leaves().len(), 4);
+    }
+
+    #[test]
+    fn split_conversion() {
+        let mut game = Game::new();
+        for &(first, leave, second) in &[
+            (Draw::Split(8), Some(Leave::new(&[7, 10])), Draw::Fool),
+            (Draw::Split(8), Some(Leave::new(&[4, 6])), Draw::Spare),
+            (Draw::Split(8), None, Draw::Open(1)),
+            (Draw::Open(8), Some(Leave::new(&[7, 10])), Draw::Open(1)),
+            (Draw::Open(8), Some(Leave::new(&[2, 4])), Draw::Spare),
+            (Draw::Open(8), Some(Leave::new(&[5, 6])), Draw::Spare),
+        ] {
+            game.set_draw(first);
+            if let Some(leave) = leave {
+                game.set_leave(leave);
+            }
+            game.set_draw(second);
+        }
+        let mut series = Series::new(2, 0);
+        series.add_game(game);
+        series.add_game(Game::from_draws(&[Draw::Split(8), Draw::Spare]));
+        let stats = SplitStats::from_series(vec![&series]);
+        assert_eq!(stats.splits, Conversion { attempts: 6, converted: 3 });
+        assert_eq!(stats.splits.percentage(), 50.);
+        assert_eq!(stats.leave(Leave::new(&[7, 10])), Conversion { attempts: 2, converted: 0 });
+        assert_eq!(stats.leave(Leave::new(&[5, 6])), Conversion { attempts: 1, converted: 1 });
+        assert_eq!(stats.most_frequent()[0].0, Leave::new(&[7, 10]));
+        assert_eq!(stats.most_frequent().len(), 3);
+    }
+
+    #[test]
+    fn strikes_by_speed_and_board() {
+        let mut game = Game::new();
+        for &(draw, board, speed) in &[
+            (Draw::Strike, 10, 171),
+            (Draw::Strike, 10, 175),
+            (Draw::Open(9), 12, 168),
+            (Draw::Strike, 12, 178),
+        ] {
+            game.set_draw(draw);
+            game.set_targeting(board, board - 2);
+            game.set_speed(speed);
+            if draw != Draw::Strike {
+                game.set_draw(Draw::Spare);
+            }
+        }
+        game.set_draw(Draw::Open(7));
+        let by_speed = strikes_by(vec![&game], |shot| shot.speed.map(|speed| speed / 10));
+        assert_eq!(by_speed[&16], Conversion { attempts: 1, converted: 0 });
+        assert_eq!(by_speed[&17], Conversion { attempts: 3, converted: 3 });
+        let by_board = strikes_by(vec![&game], |shot| shot.board);
+        assert_eq!(by_board[&12].percentage(), 50.);
+        assert_eq!(by_board.values().map(|conversion| conversion.attempts).sum::<u32>(), 4);
+    }
+}
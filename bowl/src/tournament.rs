@@ -0,0 +1,371 @@
+use std::cmp::Ordering;
+
+use bracket::Rolloff;
+use csv;
+use series::Series;
+use {Draw, Game};
+
+// Qualifying
+
+pub struct Qualifier {
+    pub name: String,
+    pub series: Series,
+}
+
+/// Tournament qualifying, bowled as a series of games with handicap
+pub struct Tournament {
+    games: usize,
+    qualifiers: Vec<Qualifier>,
+}
+
+impl Tournament {
+    /// Every qualifier bowls `games` games
+    pub fn new(games: usize) -> Tournament {
+        Tournament {
+            games,
+            qualifiers: Vec::new(),
+        }
+    }
+
+    pub fn add_qualifier(&mut self, name: &str, handicap: u32) -> usize {
+        self.qualifiers.push(Qualifier {
+            name: name.to_string(),
+            series: Series::new(self.games, handicap),
+        });
+        self.qualifiers.len() - 1
+    }
+
+    pub fn qualifiers(&self) -> &[Qualifier] {
+        self.qualifiers.as_slice()
+    }
+
+    pub fn add_game(&mut self, qualifier: usize, game: Game) {
+        self.qualifiers[qualifier].series.add_game(game);
+    }
+
+    /// Compare two qualifiers: best total, then high game, then best last
+    /// game, all with handicap, then entry order
+    fn compare(&self, a: usize, b: usize) -> Ordering {
+        let (a_series, b_series) = (&self.qualifiers[a].series, &self.qualifiers[b].series);
+        let high = |series: &Series| series.high_game().map(|pins| pins.value() + series.handicap()).unwrap_or(0);
+        let last = |series: &Series| series.entries().len().checked_sub(1)
+            .and_then(|last| series.handicap_score(last))
+            .unwrap_or(0);
+        b_series.handicap_total().value().cmp(&a_series.handicap_total().value())
+            .then(high(b_series).cmp(&high(a_series)))
+            .then(last(b_series).cmp(&last(a_series)))
+            .then(a.cmp(&b))
+    }
+
+    /// Qualifiers ranked from first to last
+    pub fn ranking(&self) -> Vec<usize> {
+        let mut ranking: Vec<usize> = (0..self.qualifiers.len()).collect();
+        ranking.sort_by(|&a, &b| self.compare(a, b));
+        ranking
+    }
+
+    /// Total with handicap needed to make a cut to `size` qualifiers
+    pub fn cut_line(&self, size: usize) -> Option<u32> {
+        self.ranking().get(size.checked_sub(1)?)
+            .map(|&qualifier| self.qualifiers[qualifier].series.handicap_total().value())
+    }
+
+    /// The `size` best qualifiers, best first
+    pub fn cut(&self, size: usize) -> Vec<usize> {
+        self.ranking().into_iter().take(size).collect()
+    }
+
+    /// Stepladder finals between the qualifiers making a cut to `size`
+    pub fn stepladder(&self, size: usize, rolloff: Rolloff) -> Stepladder {
+        let seeds: Vec<(usize, u32)> = self.cut(size).into_iter()
+            .map(|qualifier| (qualifier, self.qualifiers[qualifier].series.handicap()))
+            .collect();
+        Stepladder::new(seeds, rolloff)
+    }
+
+    /// Finishing order of the qualifying, entrants with the same total are tied
+    pub fn finish(&self) -> Vec<Vec<usize>> {
+        let mut finish: Vec<Vec<usize>> = Vec::new();
+        let total = |qualifier: usize| self.qualifiers[qualifier].series.handicap_total().value();
+        for qualifier in self.ranking() {
+            match finish.last_mut() {
+                Some(ref mut group) if total(group[0]) == total(qualifier) => group.push(qualifier),
+                _ => finish.push(vec![qualifier]),
+            }
+        }
+        finish
+    }
+
+    /// Results with prize money as CSV: place, name, total and amount
+    pub fn results_csv(&self, payouts: &[Payout]) -> String {
+        let mut csv = String::from("place,name,total,amount\n");
+        for payout in payouts {
+            let qualifier = &self.qualifiers[payout.entrant];
+            csv.push_str(&format!("{},{},{},{}.{:02}\n", payout.place, csv::field(&qualifier.name),
+                                  qualifier.series.handicap_total(), payout.amount / 100, payout.amount % 100));
+        }
+        csv
+    }
+}
+
+// Stepladder finals
+
+/// One stepladder match, `seeds.0` is the better seed waiting on the ladder
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct StepMatch {
+    pub seeds: (usize, usize),
+    /// Scores with handicap
+    pub scores: Option<(u32, u32)>,
+    pub rolloffs: Vec<(u32, u32)>,
+    pub winner: Option<usize>,
+}
+
+/// The two lowest seeds meet first, the winner climbs to meet the next seed up
+///
+/// Seeds are counted from 0, the top seed only bowls the title match.
+pub struct Stepladder {
+    seeds: Vec<(usize, u32)>,
+    rolloff: Rolloff,
+    matches: Vec<StepMatch>,
+}
+
+impl Stepladder {
+    /// `seeds` are `(qualifier, handicap)`, best seed first
+    pub fn new(seeds: Vec<(usize, u32)>, rolloff: Rolloff) -> Stepladder {
+        if seeds.len() < 2 {
+            panic!("Shouldn't append");
+        }
+        let last = seeds.len() - 1;
+        Stepladder {
+            seeds,
+            rolloff,
+            matches: vec![StepMatch {
+                seeds: (last - 1, last),
+                scores: None,
+                rolloffs: Vec::new(),
+                winner: None,
+            }],
+        }
+    }
+
+    pub fn matches(&self) -> &[StepMatch] {
+        self.matches.as_slice()
+    }
+
+    /// Seeds of the match to bowl, `None` once the title is decided
+    pub fn next_match(&self) -> Option<(usize, usize)> {
+        self.matches.last().filter(|last| last.winner.is_none()).map(|last| last.seeds)
+    }
+
+    pub fn needs_rolloff(&self) -> bool {
+        match self.matches.last() {
+            Some(last) => last.winner.is_none() && last.scores.is_some(),
+            None => false,
+        }
+    }
+
+    /// Record the finished games of the pending match, `better` is the better seed's one
+    pub fn record(&mut self, better: Game, challenger: Game) {
+        if !better.is_finished() || !challenger.is_finished() || self.needs_rolloff() {
+            panic!("Shouldn't append");
+        }
+        let (a, b) = match self.next_match() {
+            Some(seeds) => seeds,
+            None => panic!("Shouldn't append"),
+        };
+        let scores = (better.score().value() + self.seeds[a].1, challenger.score().value() + self.seeds[b].1);
+        self.matches.last_mut().unwrap().scores = Some(scores);
+        self.decide(scores);
+    }
+
+    /// Record a roll-off of a tied match
+    pub fn record_rolloff(&mut self, better: &[Draw], challenger: &[Draw]) {
+        if !self.needs_rolloff() {
+            panic!("Shouldn't append");
+        }
+        let scores = (self.rolloff.score(better), self.rolloff.score(challenger));
+        self.matches.last_mut().unwrap().rolloffs.push(scores);
+        self.decide(scores);
+    }
+
+    fn decide(&mut self, scores: (u32, u32)) {
+        let (a, b) = self.matches.last().unwrap().seeds;
+        let winner = match scores.0.cmp(&scores.1) {
+            Ordering::Greater => a,
+            Ordering::Less => b,
+            Ordering::Equal => return,
+        };
+        self.matches.last_mut().unwrap().winner = Some(winner);
+        if a > 0 {
+            self.matches.push(StepMatch {
+                seeds: (a - 1, winner),
+                scores: None,
+                rolloffs: Vec::new(),
+                winner: None,
+            });
+        }
+    }
+
+    /// Qualifier winning the title
+    pub fn champion(&self) -> Option<usize> {
+        match self.next_match() {
+            Some(_) => None,
+            None => self.matches.last().and_then(|last| last.winner).map(|seed| self.seeds[seed].0),
+        }
+    }
+
+    /// Qualifiers in finishing order once the title is decided
+    pub fn placings(&self) -> Option<Vec<usize>> {
+        let champion = self.champion()?;
+        let mut placings = vec![champion];
+        for step in self.matches.iter().rev() {
+            let loser = if step.winner == Some(step.seeds.0) { step.seeds.1 } else { step.seeds.0 };
+            placings.push(self.seeds[loser].0);
+        }
+        Some(placings)
+    }
+}
+
+// Prize fund
+
+/// Money collected for the prizes, amounts are in cents
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct PrizeFund {
+    pub entries: u32,
+    pub entry_fee: u32,
+    /// Part of each entry fee kept for lineage and expenses
+    pub expenses: u32,
+    /// One entry in `ratio` cashes
+    pub ratio: u32,
+}
+
+impl PrizeFund {
+    pub fn total(&self) -> u32 {
+        self.entries * self.entry_fee.saturating_sub(self.expenses)
+    }
+
+    pub fn cashers(&self) -> u32 {
+        match self.ratio {
+            0 => 0,
+            ratio => self.entries.div_ceil(ratio),
+        }
+    }
+
+    /// Prize of every cashing position, best first
+    ///
+    /// Each position weighs one more than the next one down, rounding leftovers
+    /// go to the first place.
+    pub fn prizes(&self) -> Vec<u32> {
+        let cashers = self.cashers();
+        let weights = cashers * (cashers + 1) / 2;
+        let mut prizes: Vec<u32> = (0..cashers)
+            .map(|position| (self.total() as u64 * (cashers - position) as u64 / weights as u64) as u32)
+            .collect();
+        let leftover = self.total() - prizes.iter().sum::<u32>();
+        if let Some(first) = prizes.first_mut() {
+            *first += leftover;
+        }
+        prizes
+    }
+
+    /// Pay the finishers, given as groups of tied entrants in finishing order
+    ///
+    /// A tied group shares the prizes of all the positions it takes.
+    pub fn payouts(&self, finish: &[Vec<usize>]) -> Vec<Payout> {
+        let prizes = self.prizes();
+        let mut payouts = Vec::new();
+        let mut position = 0;
+        for group in finish {
+            let combined: u32 = prizes.iter().skip(position).take(group.len()).sum();
+            for (index, &entrant) in group.iter().enumerate() {
+                let mut amount = combined / group.len() as u32;
+                if index == 0 {
+                    amount += combined % group.len() as u32;
+                }
+                payouts.push(Payout { place: position + 1, entrant, amount });
+            }
+            position += group.len();
+        }
+        payouts
+    }
+}
+
+/// Prize won by an entrant, `place` is shared by tied entrants
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Payout {
+    pub place: usize,
+    pub entrant: usize,
+    pub amount: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixtures::game;
+
+    #[test]
+    fn ranking_and_cut() {
+        let mut tournament = Tournament::new(2);
+        for &(name, handicap, first, second) in &[
+            ("Alice", 0, 4, 4),
+            ("Bob", 10, 4, 3),
+            ("Carol", 0, 3, 5),
+            ("Dave", 0, 2, 2),
+        ] {
+            let qualifier = tournament.add_qualifier(name, handicap);
+            tournament.add_game(qualifier, game(first));
+            tournament.add_game(qualifier, game(second));
+        }
+        // Alice, Bob and Carol are tied at 160, Carol has the high game, then Bob once handicap is added
+        assert_eq!(tournament.ranking(), vec![2, 1, 0, 3]);
+        assert_eq!(tournament.cut(2), vec![2, 1]);
+        assert_eq!(tournament.cut_line(3), Some(160));
+        assert_eq!(tournament.cut_line(0), None);
+    }
+
+    #[test]
+    fn stepladder_finals() {
+        let mut ladder = Stepladder::new(vec![(10, 0), (11, 0), (12, 0), (13, 40)], Rolloff::OneBall);
+        assert_eq!(ladder.next_match(), Some((2, 3)));
+        ladder.record(game(4), game(3));
+        assert_eq!(ladder.matches()[0].scores, Some((80, 100)));
+        assert_eq!(ladder.next_match(), Some((1, 3)));
+        ladder.record(game(4), game(2));
+        assert!(ladder.needs_rolloff());
+        ladder.record_rolloff(&[Draw::Open(8)], &[Draw::Strike]);
+        assert_eq!(ladder.next_match(), Some((0, 3)));
+        assert_eq!(ladder.champion(), None);
+        ladder.record(game(5), game(2));
+        assert_eq!(ladder.next_match(), None);
+        assert_eq!(ladder.champion(), Some(10));
+        assert_eq!(ladder.placings(), Some(vec![10, 13, 11, 12]));
+    }
+
+    #[test]
+    fn prize_fund() {
+        let fund = PrizeFund { entries: 10, entry_fee: 5000, expenses: 1000, ratio: 4 };
+        assert_eq!(fund.total(), 40000);
+        assert_eq!(fund.cashers(), 3);
+        assert_eq!(fund.prizes(), vec![20001, 13333, 6666]);
+        assert_eq!(fund.payouts(&[vec![4], vec![2, 7], vec![1]]), vec![
+            Payout { place: 1, entrant: 4, amount: 20001 },
+            Payout { place: 2, entrant: 2, amount: 10000 },
+            Payout { place: 2, entrant: 7, amount: 9999 },
+            Payout { place: 4, entrant: 1, amount: 0 },
+        ]);
+    }
+
+    #[test]
+    fn payouts_export() {
+        let mut tournament = Tournament::new(1);
+        for &(name, pins) in &[("Alice", 4), ("Bob", 5), ("Carol, \"C\"", 4)] {
+            let qualifier = tournament.add_qualifier(name, 0);
+            tournament.add_game(qualifier, game(pins));
+        }
+        assert_eq!(tournament.finish(), vec![vec![1], vec![0, 2]]);
+        let fund = PrizeFund { entries: 3, entry_fee: 2000, expenses: 500, ratio: 2 };
+        let payouts = fund.payouts(&tournament.finish());
+        assert_eq!(tournament.results_csv(&payouts),
+                   "place,name,total,amount\n1,Bob,100,30.00\n2,Alice,80,7.50\n2,\"Carol, \"\"C\"\"\",80,7.50\n");
+    }
+}
@@ -1,22 +1,38 @@
 extern crate bowl;
 
+use std::env;
 use std::io;
+use std::process;
 
 
 fn main() {
+    if let Some(code) = env::args().nth(1) {
+        match bowl::code::decode(&code) {
+            Ok(game) => println!("The score of this game is: {}", game.score()),
+            Err(error) => {
+                eprintln!("Can't read this game code: {}", error);
+                process::exit(1);
+            },
+        }
+        return;
+    }
     let mut game = bowl::Game::new();
     let reader = io::stdin();
-    'frames: loop {
-        let frame = game.next_frame();
-        loop {
-            match frame.set_draw(stdin2draw(&reader)) {
-                bowl::NextAction::NextDraw => {},
-                bowl::NextAction::NextFrame => { continue 'frames; },
-                bowl::NextAction::Finish => { break 'frames; },
-            }
+    loop {
+        let draw = stdin2draw(&reader);
+        if !game.accepts(draw) {
+            println!("	This draw can't be played here!");
+            continue;
+        }
+        if game.set_draw(draw) == bowl::NextAction::Finish {
+            break;
         }
     }
     println!("Your final score is: {}", game.score());
+    match bowl::code::encode(&game) {
+        Ok(code) => println!("Share your game with the code: {}", code),
+        Err(error) => eprintln!("Can't share this game: {}", error),
+    }
 }
 
 
@@ -25,22 +41,26 @@ fn stdin2draw(reader: &io::Stdin) -> bowl::Draw {
         let mut input = String::new();
         println!("Please enter your draw ('0' to '9', or '/' or 'X'");
         match reader.read_line(&mut input) {
+            Ok(0) => {
+                eprintln!("No more draws to read, the game is unfinished");
+                process::exit(1);
+            },
             Ok(2) => match input.chars().next() {
-                    Some('X') => {
-                        return bowl::Draw::Strike;
-                    },
-                    Some('/') => {
-                        return bowl::Draw::Spare;
-                    },
-                    Some(x @ '0' ... '9') => {
-                        if let Some(digit) = x.to_digit(10) {
-                            return bowl::Draw::Open(digit);
-                        }
-                    },
-                    _ => {},
+                Some('X') => {
+                    return bowl::Draw::Strike;
+                },
+                Some('/') => {
+                    return bowl::Draw::Spare;
+                },
+                Some(x @ '0'..='9') => {
+                    if let Some(digit) = x.to_digit(10) {
+                        return bowl::Draw::Open(digit);
+                    }
+                },
+                _ => {},
             },
             _ => {},
-        };
+        }
         println!("	Error reading your input!");
     };
 }